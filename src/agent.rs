@@ -1,8 +1,11 @@
 use std::{collections::HashSet, mem::MaybeUninit};
 
+use rand::{prelude::SliceRandom, Rng};
+use strum::EnumIter;
+
 use crate::*;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
 pub enum Sex {
     Male,
     Female,
@@ -10,26 +13,148 @@ pub enum Sex {
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum CharacterFeature {
+    // martial
+    Brave,
     Coward,
+    Strategist,
+    // diplomatic
+    Charismatic,
+    Arrogant,
+    Honest,
+    // stewardship
+    Shrewd,
+    Greedy,
+    Wasteful,
+    Just,
+    // religious
+    Pious,
+    Zealous,
+    Cynical,
+    // temperament
+    Ambitious,
+    Content,
+    // health
+    Strong,
+    Sickly,
+    Wounded,
     Idiot,
+    Genius,
+}
+
+use CharacterFeature::*;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TraitEffect {
+    Martial,
+    Diplomacy,
+    Stewardship,
+    Stability,
+    Aggression,
+    Health,
+}
+
+// a character gets at most one trait from each group at birth
+pub const TRAIT_GROUPS: [&[CharacterFeature]; 6] = [
+    &[Brave, Coward, Strategist],
+    &[Charismatic, Arrogant, Honest],
+    &[Shrewd, Greedy, Wasteful, Just],
+    &[Pious, Zealous, Cynical],
+    &[Ambitious, Content],
+    &[Strong, Sickly, Idiot, Genius],
+];
+pub const TRAIT_CHANCE: f64 = 0.3;
+
+impl CharacterFeature {
+    pub fn effects(self) -> Vec<(TraitEffect, f32)> {
+        use TraitEffect::*;
+        match self {
+            Brave => vec![(Martial, 0.15), (Aggression, 0.25)],
+            Coward => vec![(Martial, -0.25), (Aggression, -0.5)],
+            Strategist => vec![(Martial, 0.25)],
+            Charismatic => vec![(Diplomacy, 0.3)],
+            Arrogant => vec![(Diplomacy, -0.3), (Aggression, 0.25)],
+            Honest => vec![(Diplomacy, 0.15), (Stewardship, -0.05)],
+            Shrewd => vec![(Stewardship, 0.2)],
+            Greedy => vec![(Stewardship, 0.15), (Stability, -0.15)],
+            Wasteful => vec![(Stewardship, -0.2)],
+            Just => vec![(Stability, 0.2)],
+            Pious => vec![(Stability, 0.15)],
+            Zealous => vec![(Stability, 0.1), (Diplomacy, -0.15), (Aggression, 0.25)],
+            Cynical => vec![(Stability, -0.1)],
+            Ambitious => vec![(Aggression, 0.5)],
+            Content => vec![(Aggression, -0.5)],
+            Strong => vec![(Health, 10.0), (Martial, 0.1)],
+            Sickly => vec![(Health, -15.0)],
+            Wounded => vec![(Health, -10.0), (Martial, -0.05)],
+            Idiot => vec![(Martial, -0.15), (Stewardship, -0.2), (Diplomacy, -0.1)],
+            Genius => vec![(Martial, 0.1), (Stewardship, 0.2), (Diplomacy, 0.1)],
+        }
+    }
+
+    pub fn opposite(self) -> Option<Self> {
+        match self {
+            Brave => Some(Coward),
+            Coward => Some(Brave),
+            Shrewd => Some(Wasteful),
+            Wasteful => Some(Shrewd),
+            Pious | Zealous => Some(Cynical),
+            Cynical => Some(Pious),
+            Ambitious => Some(Content),
+            Content => Some(Ambitious),
+            Strong => Some(Sickly),
+            Sickly => Some(Strong),
+            Idiot => Some(Genius),
+            Genius => Some(Idiot),
+            _ => None,
+        }
+    }
+
+    // born with rather than learned
+    pub fn congenital(self) -> bool {
+        matches!(self, Strong | Sickly | Idiot | Genius)
+    }
+
+    pub fn random_features(rng: &mut impl Rng) -> HashSet<CharacterFeature> {
+        let mut features = HashSet::new();
+        for group in TRAIT_GROUPS.iter() {
+            if rng.gen::<f64>() < TRAIT_CHANCE {
+                features.extend(group.choose(rng).cloned());
+            }
+        }
+        features
+    }
+
+    pub fn health_effect(features: &HashSet<CharacterFeature>) -> f32 {
+        features
+            .iter()
+            .flat_map(|f| f.effects())
+            .filter(|(effect, _)| *effect == TraitEffect::Health)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
 }
 
 pub enum Title {
     PolityLeader(PolityId),
     SettlementLeader(SettlementId),
+    ReligiousHead(ReligionId),
 }
 
 impl Title {
-    pub fn name(&self) -> String {
+    pub fn name(&self, sex: Sex) -> String {
         match self {
             Title::PolityLeader(polity_id) => {
                 let polity = polity_id.get();
-                format!("{} of {}", polity.level.leader_title(), polity.name)
+                format!("{} of {}", polity.level.leader_title(sex), polity.name)
             },
             Title::SettlementLeader(settlement_id) => {
                 let settlement = settlement_id.get();
                 format!("Mayor of {}", settlement.name)
             },
+            Title::ReligiousHead(religion_id) => {
+                let title = if sex == Sex::Female { "High Priestess" } else { "High Priest" };
+                format!("{} of {}", title, religion_id.get().name)
+            },
         }
     }
 }
@@ -42,8 +167,16 @@ pub struct Character {
     pub sex: Sex,
     pub health: f32,
     pub death: Option<Date>,
+    pub cause_of_death: Option<CauseOfDeath>,
     pub features: HashSet<CharacterFeature>,
     pub titles: Vec<Title>,
+    pub prestige: f32,
+    pub culture: CultureId,
+    pub dynasty: Option<DynastyId>,
+    pub father: Option<CharacterId>,
+    pub mother: Option<CharacterId>,
+    pub spouse: Option<CharacterId>,
+    pub children: Vec<CharacterId>,
 }
 
 gen_id!(Character, CharacterId);
@@ -63,9 +196,38 @@ impl Featured<CharacterFeature> for Character {
 }
 
 impl Character {
+    pub fn trait_modifier(&self, effect: TraitEffect) -> f32 {
+        self.features
+            .iter()
+            .flat_map(|f| f.effects())
+            .filter(|(e, _)| *e == effect)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    // how much this character helps or hinders an army they lead
+    pub fn martial_modifier(&self) -> f32 {
+        (1.0 + self.trait_modifier(TraitEffect::Martial)).max(0.25)
+    }
+
+    // returns false if the character already has the trait or its opposite
+    pub fn gain_feature(&mut self, feature: CharacterFeature) -> bool {
+        if self.has_feature(feature) || feature.opposite().map(|o| self.has_feature(o)).unwrap_or(false) {
+            return false;
+        }
+        self.add_feature(feature);
+        self.health += feature
+            .effects()
+            .iter()
+            .filter(|(effect, _)| *effect == TraitEffect::Health)
+            .map(|(_, amount)| amount)
+            .sum::<f32>();
+        true
+    }
+
     pub fn title(&self, world: &World) -> String {
-        let titles = self.titles.iter().map(|t| t.name()).collect::<Vec<_>>();
-        let titles_str = if titles.len() > 0 {
+        let titles = self.titles.iter().map(|t| t.name(self.sex)).collect::<Vec<_>>();
+        let titles_str = if !titles.is_empty() {
             format!(", {},", titles.join(", "))
         } else {
             "".to_owned()
@@ -75,6 +237,49 @@ impl Character {
     }
 }
 
+// rulers lend their traits to the polity and settlement they govern
+pub fn update_ruler_factors(world: &World) {
+    let polity_factors = [
+        (TraitEffect::Diplomacy, FactorType::PolityDiplomacy),
+        (TraitEffect::Stewardship, FactorType::PolityStewardship),
+        (TraitEffect::Stability, FactorType::PolityStability),
+        (TraitEffect::Aggression, FactorType::PolityAggression),
+    ];
+    for polity in world.iter_storage::<Polity>() {
+        let ruler = polity.get().ruler();
+        for (effect, ftype) in polity_factors.iter() {
+            let modifier = ruler.get().trait_modifier(*effect) + religion_factor(polity, *effect, world);
+            world.formula_system.insert_factor(&(polity.gid(), *ftype), modifier);
+        }
+    }
+    for settlement in world.iter_storage::<Settlement>() {
+        let mut stability = settlement.get().headman.get().trait_modifier(TraitEffect::Stability);
+        if settlement.get().buildings.contains(&Building::Temple) {
+            stability += TEMPLE_STABILITY;
+        }
+        world.formula_system.insert_factor(&(settlement.gid(), FactorType::SettlementStability), stability);
+    }
+}
+
+pub struct GainTraitCommand {
+    pub character: CharacterId,
+    pub feature: CharacterFeature,
+}
+
+impl Command for GainTraitCommand {
+    fn run(&self, world: &mut World) {
+        if !self.character.get().alive() || !self.character.get_mut().gain_feature(self.feature) {
+            return;
+        }
+        if !self.character.get().titles.is_empty() {
+            world.events.add(Rc::new(TraitGainedEvent {
+                character: self.character.clone(),
+                feature: self.feature,
+            }));
+        }
+    }
+}
+
 pub trait Agent {
     fn decide();
 }
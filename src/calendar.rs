@@ -0,0 +1,249 @@
+use std::fmt::{Debug, Display};
+
+use crate::*;
+
+pub const DAYS_PER_MONTH: isize = 30;
+pub const MONTHS_PER_YEAR: isize = 12;
+pub const DAYS_PER_YEAR: isize = DAYS_PER_MONTH * MONTHS_PER_YEAR;
+
+pub const MONTH_NAMES: [&str; 12] = [
+    "Deepwinter",
+    "Thaw",
+    "Seedtime",
+    "Rains",
+    "Blossom",
+    "Highsun",
+    "Haymonth",
+    "Harvest",
+    "Vintage",
+    "Leaffall",
+    "Frost",
+    "Midwinter",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Season {
+    Winter,
+    Spring,
+    Summer,
+    Autumn,
+}
+
+// years count down to the epoch and up after it, with no year zero between;
+// the epoch itself is fixed, games pick where they start with `start_year` or a scenario's `year`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Era {
+    BeforeEpoch,
+    AfterEpoch,
+}
+
+impl Era {
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Era::BeforeEpoch => "BC",
+            Era::AfterEpoch => "AE",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DateFormat {
+    // 08/15/1200 BC
+    Numeric,
+    // 15 Harvest 1200 BC
+    Short,
+    // 15 Harvest 1200 BC, summer
+    Long,
+    // 1200 BC
+    Year,
+}
+
+// days counted from the first day of 1 AE, negative before it
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub day: isize,
+}
+
+impl Date {
+    // the first day of a year, -1200 being 1200 BC
+    pub fn from_year(year: isize) -> Self {
+        let index = if year < 0 { year } else { year.max(1) - 1 };
+        Date { day: index * DAYS_PER_YEAR }
+    }
+
+    // `-1200`, `1200 BC` or `1200 AE`
+    pub fn parse_year(value: &str) -> Result<isize, String> {
+        let (number, era) = match value.trim().split_once(' ') {
+            Some((number, era)) => (number, Some(era.trim())),
+            None => (value.trim(), None),
+        };
+        let year: isize = number.parse().map_err(|_| format!("bad year '{}'", value))?;
+        let year = match era {
+            None => year,
+            Some(era) if era.eq_ignore_ascii_case(Era::AfterEpoch.abbreviation()) => year,
+            Some(era) if era.eq_ignore_ascii_case(Era::BeforeEpoch.abbreviation()) => -year,
+            Some(era) => return Err(format!("unknown era '{}'", era)),
+        };
+        if year == 0 {
+            return Err("there is no year zero".to_owned());
+        }
+        Ok(year)
+    }
+
+    pub fn is_month(&self) -> bool {
+        self.day.rem_euclid(DAYS_PER_MONTH) == 0
+    }
+
+    pub fn is_year(&self) -> bool {
+        self.day.rem_euclid(DAYS_PER_YEAR) == 0
+    }
+
+    // 1 to 12
+    pub fn month(&self) -> usize {
+        (self.day_of_year() / DAYS_PER_MONTH as usize) + 1
+    }
+
+    pub fn month_name(&self) -> &'static str {
+        MONTH_NAMES[self.month() - 1]
+    }
+
+    pub fn season(&self) -> Season {
+        match self.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    // negative years are before the epoch
+    pub fn year(&self) -> isize {
+        let index = self.day.div_euclid(DAYS_PER_YEAR);
+        if index < 0 {
+            index
+        } else {
+            index + 1
+        }
+    }
+
+    pub fn era(&self) -> Era {
+        if self.day < 0 {
+            Era::BeforeEpoch
+        } else {
+            Era::AfterEpoch
+        }
+    }
+
+    pub fn day_of_month(&self) -> usize {
+        self.day.rem_euclid(DAYS_PER_MONTH) as usize + 1
+    }
+
+    // 0 to 359
+    pub fn day_of_year(&self) -> usize {
+        self.day.rem_euclid(DAYS_PER_YEAR) as usize
+    }
+
+    // whole years from this date to `now`, zero if `now` comes first
+    pub fn age(&self, now: Date) -> usize {
+        (now.day - self.day).max(0) as usize / DAYS_PER_YEAR as usize
+    }
+
+    pub fn days_until(&self, later: Date) -> isize {
+        later.day - self.day
+    }
+
+    pub fn add_days(self, days: isize) -> Self {
+        Date { day: self.day + days }
+    }
+
+    pub fn format(&self, format: DateFormat) -> String {
+        let year = format!("{} {}", self.year().abs(), self.era().abbreviation());
+        match format {
+            DateFormat::Numeric => format!("{:02}/{:02}/{}", self.month(), self.day_of_month(), year),
+            DateFormat::Short => format!("{} {} {}", self.day_of_month(), self.month_name(), year),
+            DateFormat::Long => {
+                let season = format!("{:?}", self.season()).to_lowercase();
+                format!("{} {} {}, {}", self.day_of_month(), self.month_name(), year, season)
+            },
+            DateFormat::Year => year,
+        }
+    }
+}
+
+impl Debug for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(DateFormat::Numeric))
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(DateFormat::Short))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn years_skip_zero_around_the_epoch() {
+        assert_eq!(Date { day: 0 }.year(), 1);
+        assert_eq!(Date { day: -1 }.year(), -1);
+        assert_eq!(Date { day: DAYS_PER_YEAR - 1 }.year(), 1);
+        assert_eq!(Date { day: DAYS_PER_YEAR }.year(), 2);
+        assert_eq!(Date { day: -DAYS_PER_YEAR }.year(), -1);
+        assert_eq!(Date { day: -DAYS_PER_YEAR - 1 }.year(), -2);
+        assert_eq!(Date { day: -1 }.era(), Era::BeforeEpoch);
+        assert_eq!(Date { day: 0 }.era(), Era::AfterEpoch);
+    }
+
+    #[test]
+    fn from_year_starts_the_year() {
+        for year in [-1200, -2, -1, 1, 2, 800] {
+            let date = Date::from_year(year);
+            assert_eq!(date.year(), year);
+            assert_eq!(date.day_of_year(), 0);
+            let previous = if year == 1 { -1 } else { year - 1 };
+            assert_eq!(date.add_days(-1).year(), previous);
+        }
+        assert_eq!(Date::from_year(1), Date { day: 0 });
+        assert_eq!(Date::from_year(-1), Date { day: -DAYS_PER_YEAR });
+    }
+
+    #[test]
+    fn parse_year_reads_eras() {
+        assert_eq!(Date::parse_year("1200 BC"), Ok(-1200));
+        assert_eq!(Date::parse_year("12 ae"), Ok(12));
+        assert_eq!(Date::parse_year(" -1 "), Ok(-1));
+        assert_eq!(Date::parse_year("1"), Ok(1));
+        assert!(Date::parse_year("0").is_err());
+        assert!(Date::parse_year("0 BC").is_err());
+        assert!(Date::parse_year("12 AD").is_err());
+        assert!(Date::parse_year("twelve").is_err());
+    }
+
+    #[test]
+    fn age_counts_whole_years_and_never_goes_negative() {
+        let birthday = Date::from_year(-1).add_days(100);
+        assert_eq!(birthday.age(birthday), 0);
+        assert_eq!(birthday.age(birthday.add_days(DAYS_PER_YEAR - 1)), 0);
+        assert_eq!(birthday.age(birthday.add_days(DAYS_PER_YEAR)), 1);
+        assert_eq!(birthday.age(Date::from_year(3)), 2);
+        assert_eq!(birthday.age(birthday.add_days(-DAYS_PER_YEAR)), 0);
+    }
+
+    #[test]
+    fn format_around_the_epoch() {
+        let last_day = Date { day: -1 };
+        assert_eq!(last_day.format(DateFormat::Numeric), "12/30/1 BC");
+        assert_eq!(last_day.format(DateFormat::Short), "30 Midwinter 1 BC");
+        assert_eq!(last_day.format(DateFormat::Long), "30 Midwinter 1 BC, winter");
+        assert_eq!(last_day.format(DateFormat::Year), "1 BC");
+        let first_day = last_day.add_days(1);
+        assert_eq!(first_day.format(DateFormat::Numeric), "01/01/1 AE");
+        assert_eq!(first_day.format(DateFormat::Short), "1 Deepwinter 1 AE");
+        let harvest = Date::from_year(-1200).add_days(7 * DAYS_PER_MONTH + 14);
+        assert_eq!(harvest.format(DateFormat::Long), "15 Harvest 1200 BC, summer");
+    }
+}
@@ -151,12 +151,17 @@ impl Command for PopEatCommand {
     }
 }
 
-pub struct KillCharacterCommand(pub CharacterId);
+pub struct KillCharacterCommand(pub CharacterId, pub CauseOfDeath);
 
 impl Command for KillCharacterCommand {
     fn run(&self, world: &mut World) {
+        if !self.0.get().alive() {
+            return;
+        }
         self.0.get_mut().death = Some(world.date);
+        self.0.get_mut().cause_of_death = Some(self.1);
         // trigger succession events
+        world.events.add(Rc::new(CharacterDiedEvent(self.0.clone())));
     }
 }
 
@@ -164,41 +169,206 @@ pub struct PolityUpdateLeaderCommand(pub PolityId);
 
 impl Command for PolityUpdateLeaderCommand {
     fn run(&self, world: &mut World) {
-        let leader = match &self.0.get().successor_law {
-            SuccessorLaw::Election => self.0.get().primary_culture.get().generate_character(Sex::Male, positive_isample(8, 45), world),
-            SuccessorLaw::Inheritance(heir) => heir.clone(),
-        };
         let old_leader = self.0.get().leader.clone();
+        let law = self.0.get().successor_law;
+        let mut gender = self.0.get().gender_law;
+        let mut heir = select_heir(&old_leader, law, gender, world);
+        // a ruler with only daughters may bend the law for them
+        let has_daughters = !old_leader.get().living_children(Some(Sex::Female)).is_empty();
+        if heir.is_none() && law != SuccessorLaw::Election && gender == GenderLaw::Agnatic && has_daughters && individual_event(0.3) {
+            gender = GenderLaw::Cognatic;
+            ChangeGenderLawCommand {
+                polity: self.0.clone(),
+                law: gender,
+            }
+            .run(world);
+            heir = select_heir(&old_leader, law, gender, world);
+        }
+        // when the line fails the notables choose
+        let leader = match heir {
+            Some(heir) => heir,
+            None => elect_polity_leader(&self.0, &old_leader, world),
+        };
+        found_dynasty(&leader, world);
         self.0.get_mut().leader = leader.clone();
+        self.0.get_mut().regent = None;
         leader.get_mut().titles.push(Title::PolityLeader(self.0.clone()));
+        if !leader.get().is_adult(world.date) {
+            let regent = choose_regent(&leader, world);
+            self.0.get_mut().regent = Some(regent.clone());
+            world.events.add(Rc::new(RegencyEvent {
+                polity: self.0.clone(),
+                ruler: leader.clone(),
+                regent,
+            }));
+        }
+        if law == SuccessorLaw::Partible {
+            PartitionInheritanceCommand {
+                polity: self.0.clone(),
+                deceased: old_leader.clone(),
+            }
+            .run(world);
+        }
         // println!("change leader: {} to {}", old_leader.get().title(world), leader.get().title(world));
     }
 }
 
+pub struct ChangeGenderLawCommand {
+    pub polity: PolityId,
+    pub law: GenderLaw,
+}
+
+impl Command for ChangeGenderLawCommand {
+    fn run(&self, world: &mut World) {
+        let from = self.polity.get().gender_law;
+        if from == self.law {
+            return;
+        }
+        self.polity.get_mut().gender_law = self.law;
+        world.events.add(Rc::new(GenderLawChangedEvent {
+            polity: self.polity.clone(),
+            from,
+            to: self.law,
+        }));
+    }
+}
+
 pub struct SettlementUpdateHeadmanCommand(pub SettlementId);
 
 impl Command for SettlementUpdateHeadmanCommand {
     fn run(&self, world: &mut World) {
-        let headman = match &self.0.get().successor_law {
-            SuccessorLaw::Election => self.0.get().primary_culture.get().generate_character(Sex::Male, positive_isample(8, 45), world),
-            SuccessorLaw::Inheritance(heir) => heir.clone(),
-        };
         let old_headman = self.0.get().headman.clone();
-        self.0.get_mut().headman = headman.clone();
-        headman.get_mut().titles.push(Title::SettlementLeader(self.0.clone()));
+        let law = self.0.get().successor_law;
+        let gender = self.0.get().controller.get().gender_law;
+        let headman = match select_heir(&old_headman, law, gender, world) {
+            Some(heir) => heir,
+            None => elect_headman(&self.0, &old_headman, world),
+        };
+        SetHeadmanCommand {
+            settlement: self.0.clone(),
+            headman,
+        }
+        .run(world);
         // println!("{:?} change headman: {} to {}", world.date, old_headman.get().title(world), headman.get().title(world));
     }
 }
 
+// the title moves along with the settlement
+pub struct SetHeadmanCommand {
+    pub settlement: SettlementId,
+    pub headman: CharacterId,
+}
+
+impl Command for SetHeadmanCommand {
+    fn run(&self, world: &mut World) {
+        let old_headman = self.settlement.get().headman.clone();
+        old_headman
+            .get_mut()
+            .titles
+            .retain(|t| !matches!(t, Title::SettlementLeader(s) if *s == self.settlement));
+        self.settlement.get_mut().headman = self.headman.clone();
+        self.headman.get_mut().titles.push(Title::SettlementLeader(self.settlement.clone()));
+    }
+}
+
 pub struct DestroySettlementCommand(pub SettlementId);
 
 impl Command for DestroySettlementCommand {
     fn run(&self, world: &mut World) {
+        let controller = self.0.get().controller.clone();
+        let was_capital = controller.get().capital.as_ref() == Some(&self.0);
         // where do we keep track of settlements?
         self.0.get().province.get_mut().settlements.retain(|s| *s != self.0);
-        if Some(self.0.clone()) == self.0.get().controller.get().capital {
-            println!("polity over? move capital?");
+        // whoever is left behind scatters along with it
+        let pops = std::mem::take(&mut self.0.get_mut().pops);
+        for pop in pops.iter() {
+            world.remove(pop);
         }
+        let headman = self.0.get().headman.clone();
+        headman.get_mut().titles.retain(|t| !matches!(t, Title::SettlementLeader(s) if *s == self.0));
+        world.remove(&self.0);
+        if was_capital {
+            RelocateCapitalCommand(controller).run(world);
+        }
+    }
+}
+
+// move the capital to the largest remaining settlement, or dissolve if there are none
+pub struct RelocateCapitalCommand(pub PolityId);
+
+impl Command for RelocateCapitalCommand {
+    fn run(&self, world: &mut World) {
+        let old_capital = self.0.get_mut().capital.take();
+        let new_capital = self.0.get()
+            .settlements(world)
+            .into_iter()
+            .max_by_key(|s| s.get().population(world));
+        if let Some(new_capital) = new_capital {
+            self.0.get_mut().capital = Some(new_capital.clone());
+            world.events.add(Rc::new(PolityCapitalMovedEvent {
+                polity: self.0.clone(),
+                old_capital,
+                new_capital,
+            }));
+        } else {
+            DissolvePolityCommand(self.0.clone()).run(world);
+        }
+    }
+}
+
+pub struct DissolvePolityCommand(pub PolityId);
+
+impl Command for DissolvePolityCommand {
+    fn run(&self, world: &mut World) {
+        // anything still claimed by the polity goes over to its people's other rulers or a neighbor,
+        // or else breaks away on its own
+        for settlement in self.0.get().settlements(world) {
+            let from_pops = settlement
+                .get()
+                .pops
+                .iter()
+                .map(|p| p.get().polity.clone())
+                .find(|p| *p != self.0 && world.contains(p));
+            let province = settlement.get().province.clone();
+            let from_neighbors = || {
+                world
+                    .neighbors(&province)
+                    .iter()
+                    .filter_map(|p| p.get().controller.clone())
+                    .find(|p| *p != self.0)
+            };
+            let new_controller = match from_pops.or_else(from_neighbors) {
+                Some(new_controller) => new_controller,
+                None => {
+                    let culture = settlement.get().primary_culture.clone();
+                    let name = culture.get().language.get().generate_toponym();
+                    let successor = add_polity(world, name, culture, PolityLevel::Tribe, &mut thread_rng());
+                    successor.get_mut().capital = Some(settlement.clone());
+                    successor
+                }
+            };
+            settlement.get_mut().controller = new_controller;
+        }
+        for pop in world.iter_storage::<Pop>() {
+            let settlement = pop.get().settlement.clone();
+            if pop.get().polity == self.0 && world.contains(&settlement) {
+                let controller = settlement.get().controller.clone();
+                pop.get_mut().polity = controller;
+            }
+        }
+        debug_assert!(
+            world.iter_storage::<Settlement>().all(|s| s.get().controller != self.0)
+                && world.iter_storage::<Pop>().all(|p| p.get().polity != self.0),
+            "dissolved polity {} is still referenced",
+            self.0.get().name
+        );
+        for province in self.0.get().provinces(world) {
+            province.get_mut().controller = None;
+        }
+        let leader = self.0.get().leader.clone();
+        leader.get_mut().titles.retain(|t| !matches!(t, Title::PolityLeader(p) if *p == self.0));
+        world.diplomacy.forget(&self.0);
+        world.events.add(Rc::new(PolityDissolvedEvent(self.0.clone())));
         world.remove(&self.0);
     }
 }
@@ -207,12 +377,13 @@ pub struct DestroyPopCommand(pub PopId);
 
 impl Command for DestroyPopCommand {
     fn run(&self, world: &mut World) {
-        self.0.get().settlement.get_mut().pops.retain(|p| *p != self.0);
-        if self.0.get().settlement.get().pops.len() == 0 {
-            // println!("settlement abandoned! {}", self.0.get().settlement.get().name);
-            DestroySettlementCommand(self.0.get().settlement.clone()).run(world);
-        }
+        let settlement = self.0.get().settlement.clone();
+        settlement.get_mut().pops.retain(|p| *p != self.0);
         world.remove(&self.0);
+        if settlement.get().pops.is_empty() {
+            // println!("settlement abandoned! {}", settlement.get().name);
+            DestroySettlementCommand(settlement).run(world);
+        }
     }
 }
 
@@ -229,7 +400,7 @@ pub struct ZoomCameraCommand(pub f32);
 
 impl Command for ZoomCameraCommand {
     fn run(&self, world: &mut World) {
-        world.camera.zoom = (world.camera.zoom * self.0).max(0.1).min(MAP_SIZE as f32 / 50.0);
+        world.camera.zoom = (world.camera.zoom * self.0).max(0.1).min(world.map_size.0.max(world.map_size.1) as f32 / 50.0);
     }
 }
 
@@ -251,22 +422,26 @@ impl Command for PopSeekMigrationCommand {
             }
             if let Some(target_province_id) = world.get_province_coordinate(random_point) {
                 let mut target_value = target_province_id.get().base_living_target_value();
+                if target_province_id.get().coastal && self.pop.get().culture.get().has_feature(CultureFeature::Seafaring) {
+                    target_value += 1.0;
+                }
 
                 for settlement in target_province_id.get().settlements.iter() {
                     target_value -= 1.0;
-                    if settlement.get().primary_culture != self.pop.get().culture {
-                        target_value -= 2.0;
-                    } else {
+                    let affinity = culture_affinity(&settlement.get().primary_culture, &self.pop.get().culture);
+                    target_value -= 2.0 * (1.0 - affinity) as f64;
+                    // strangers are less often taken in
+                    if individual_event(affinity as f64) {
                         let settlement_carrying_capacity = settlement.get().carrying_capacity(world);
                         if (settlement.get().population(world) as f32) < settlement_carrying_capacity / 4.0 {
                             let size = (self.pop.get().size / 4).min((settlement_carrying_capacity / 4.0).round() as isize);
                             self.pop.get_mut().migration_status = Some(MigrationStatus {
                                 migrating: size,
                                 dest: target_province_id.clone(),
-                                date: world.date.day + 60,
+                                date: world.date.add_days(60),
                                 settlement: Some(settlement.clone()),
                             });
-                            world.events.add_deferred(Rc::new(MigrationDoneEvent(self.pop.clone())), world.date.day + 60);
+                            world.events.add_deferred(Rc::new(MigrationDoneEvent(self.pop.clone())), world.date.add_days(60));
                             return;
                         }
                     }
@@ -277,10 +452,10 @@ impl Command for PopSeekMigrationCommand {
                     self.pop.get_mut().migration_status = Some(MigrationStatus {
                         migrating: size,
                         dest: target_province_id.clone(),
-                        date: world.date.day + 60,
+                        date: world.date.add_days(60),
                         settlement: None,
                     });
-                    world.events.add_deferred(Rc::new(MigrationDoneEvent(self.pop.clone())), world.date.day + 60);
+                    world.events.add_deferred(Rc::new(MigrationDoneEvent(self.pop.clone())), world.date.add_days(60));
                 }
             }
         }
@@ -300,7 +475,7 @@ impl Command for PopMigrateCommand {
         if let Some(settlement_id) = self.settlement.clone() {
             settlement_id.get_mut().accept_migrants(world, self.pop.clone(), self.migrating);
         } else {
-            add_settlement(world, self.pop.get().culture.clone(), self.dest.clone(), self.pop.get().polity.clone(), self.migrating);
+            add_settlement(world, self.pop.get().culture.clone(), self.dest.clone(), self.pop.get().polity.clone(), self.migrating, &mut thread_rng());
         }
         self.pop.get_mut().size -= self.migrating;
     }
@@ -0,0 +1,140 @@
+use std::{fmt::Debug, str::FromStr};
+
+use strum::IntoEnumIterator;
+
+use crate::*;
+
+// a small ini dialect: `[section]` headers followed by `key = value` lines,
+// with `#` starting a comment
+#[derive(Debug, Clone)]
+pub struct IniSection {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl IniSection {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+pub fn parse_ini(text: &str) -> Result<Vec<IniSection>, String> {
+    let mut sections: Vec<IniSection> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            if !line.ends_with(']') || line.len() < 3 {
+                return Err(format!("line {}: bad section header '{}'", n + 1, line));
+            }
+            sections.push(IniSection {
+                name: line[1..line.len() - 1].trim().to_owned(),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err(format!("line {}: expected 'key = value', got '{}'", n + 1, line)),
+        };
+        match sections.last_mut() {
+            Some(section) => section.entries.push((key.to_owned(), value.to_owned())),
+            None => return Err(format!("line {}: '{}' is outside of any section", n + 1, key)),
+        }
+    }
+    Ok(sections)
+}
+
+pub fn parse_value<T>(key: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    value
+        .parse::<T>()
+        .map_err(|e| format!("bad value '{}' for {}: {:?}", value, key, e))
+}
+
+// matches an enum variant by its name, ignoring case and underscores
+pub fn parse_variant<T>(key: &str, value: &str) -> Result<T, String>
+where
+    T: IntoEnumIterator + Debug,
+{
+    let wanted = value.replace('_', "").to_lowercase();
+    T::iter()
+        .find(|v| format!("{:?}", v).to_lowercase() == wanted)
+        .ok_or_else(|| format!("bad value '{}' for {}", value, key))
+}
+
+// comma separated, empty entries skipped
+pub fn parse_list(value: &str) -> Vec<&str> {
+    value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()).collect()
+}
+
+// written as `x y`
+pub fn parse_coordinate(key: &str, value: &str) -> Result<Coordinate, String> {
+    let parts = value.split_whitespace().collect::<Vec<_>>();
+    if parts.len() != 2 {
+        return Err(format!("bad coordinate '{}' for {}", value, key));
+    }
+    Ok(Coordinate::new(parse_value(key, parts[0])?, parse_value(key, parts[1])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ini_reads_sections_and_skips_comments() {
+        let text = "# a comment\n[first]\nkey = value # trailing\n\n[ second one ]\na=1\nb = two words\n";
+        let sections = parse_ini(text).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].name, "first");
+        assert_eq!(sections[0].get("key"), Some("value"));
+        assert_eq!(sections[1].name, "second one");
+        assert_eq!(sections[1].get("a"), Some("1"));
+        assert_eq!(sections[1].get("b"), Some("two words"));
+        assert_eq!(sections[1].get("key"), None);
+    }
+
+    #[test]
+    fn parse_ini_keeps_repeated_keys_in_order() {
+        let sections = parse_ini("[map]\nrow = ~~\nrow = ..\n").unwrap();
+        let rows = sections[0].entries.iter().map(|(_, v)| v.as_str()).collect::<Vec<_>>();
+        assert_eq!(rows, vec!["~~", ".."]);
+        assert_eq!(sections[0].get("row"), Some("~~"));
+    }
+
+    #[test]
+    fn parse_ini_rejects_malformed_lines() {
+        assert!(parse_ini("key = value\n").unwrap_err().starts_with("line 1:"));
+        assert!(parse_ini("[section\n").unwrap_err().starts_with("line 1:"));
+        assert!(parse_ini("[]\n").is_err());
+        assert!(parse_ini("[section]\n\njust words\n").unwrap_err().starts_with("line 3:"));
+    }
+
+    #[test]
+    fn parse_variant_ignores_case_and_underscores() {
+        assert_eq!(parse_variant::<Terrain>("terrain", "Hills"), Ok(Terrain::Hills));
+        assert_eq!(parse_variant::<Terrain>("terrain", "MOUNTAINS"), Ok(Terrain::Mountains));
+        assert_eq!(parse_variant::<PolityLevel>("level", "city_state"), Ok(PolityLevel::CityState));
+        assert_eq!(parse_variant::<Terrain>("terrain", "lava"), Err("bad value 'lava' for terrain".to_owned()));
+    }
+
+    #[test]
+    fn parse_value_names_the_key() {
+        assert_eq!(parse_value::<isize>("width", "12"), Ok(12));
+        assert!(parse_value::<isize>("width", "wide").unwrap_err().contains("for width"));
+    }
+
+    #[test]
+    fn parse_list_and_coordinate() {
+        assert_eq!(parse_list(" fertile, , natural_harbor ,"), vec!["fertile", "natural_harbor"]);
+        assert!(parse_list("").is_empty());
+        assert_eq!(parse_coordinate("province", "-1  10"), Ok(Coordinate::new(-1, 10)));
+        assert!(parse_coordinate("province", "1").is_err());
+        assert!(parse_coordinate("province", "1 2 3").is_err());
+    }
+}
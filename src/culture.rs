@@ -0,0 +1,207 @@
+use rand::Rng;
+use strum::EnumIter;
+
+use crate::*;
+
+// share of a minority that takes up the majority's ways each year
+pub const ASSIMILATION_RATE: f32 = 0.03;
+// how much of a culture's martial past is remembered each year
+pub const MARTIAL_MEMORY: f32 = 0.9;
+pub const WARRIOR_HISTORY: f32 = 8.0;
+pub const SEAFARING_SHARE: f32 = 0.4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
+pub enum CultureFeature {
+    Warrior,
+    Seafaring,
+}
+
+#[iron_data]
+pub struct Culture {
+    pub id: usize,
+    pub name: String,
+    pub religion: ReligionId,
+    pub language: LanguageId,
+    pub features: Vec<CultureFeature>,
+    pub gender_law: GenderLaw,
+    pub parent: Option<CultureId>,
+    pub founded: Date,
+    // battles won, fading with time
+    pub martial_history: f32,
+}
+
+impl Culture {
+    pub fn generate_character(&self, sex: Sex, age: isize, world: &mut World, rng: &mut impl Rng) -> CharacterId {
+        let culture = self.id(world);
+        let features = CharacterFeature::random_features(rng);
+        let health = dev_mean_sample_with(5.0, 60.0, rng) as f32 + CharacterFeature::health_effect(&features);
+        world.insert(Character {
+            id: 0,
+            name: format!("{} {}", self.language.get().generate_given_name(sex), self.language.get().generate_dynasty_name()),
+            birthday: world.date.add_days(-(DAYS_PER_YEAR * age + rng.gen_range(0..DAYS_PER_YEAR - 1))),
+            sex,
+            health,
+            death: None,
+            cause_of_death: None,
+            features,
+            titles: Vec::new(),
+            prestige: 0.0,
+            culture,
+            dynasty: None,
+            father: None,
+            mother: None,
+            spouse: None,
+            children: Vec::new(),
+        })
+    }
+
+    pub fn has_feature(&self, feature: CultureFeature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+// the oldest ancestor of a culture names its group
+pub fn culture_group(culture: &CultureId) -> CultureId {
+    let mut root = culture.clone();
+    loop {
+        let parent = root.get().parent.clone();
+        match parent {
+            Some(parent) => root = parent,
+            None => return root,
+        }
+    }
+}
+
+fn related(a: &CultureId, b: &CultureId) -> bool {
+    a.get().parent.as_ref() == Some(b) || b.get().parent.as_ref() == Some(a) || (a.get().parent.is_some() && a.get().parent == b.get().parent)
+}
+
+// 1 for the same culture, 0 for strangers
+pub fn culture_affinity(a: &CultureId, b: &CultureId) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    let mut affinity = 0.0;
+    if culture_group(a) == culture_group(b) {
+        affinity += 0.5;
+        if related(a, b) {
+            affinity += 0.25;
+        }
+    }
+    if a.get().religion == b.get().religion {
+        affinity += 0.1;
+    }
+    affinity
+}
+
+pub fn culture_tick(world: &World) {
+    for settlement in world.iter_storage::<Settlement>() {
+        if settlement.get().pops.len() > 1 {
+            world.add_command(Box::new(AssimilationCommand(settlement.clone())));
+        }
+    }
+    for culture in world.iter_storage::<Culture>() {
+        world.add_command(Box::new(UpdateCultureFeaturesCommand(culture.clone())));
+    }
+}
+
+pub struct AssimilationCommand(pub SettlementId);
+
+impl Command for AssimilationCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.0) {
+            return;
+        }
+        let population = self.0.get().population(world) as f32;
+        if population <= 0.0 {
+            return;
+        }
+        // a minority that outgrows the old majority takes over the settlement
+        let largest = self.0.get().pops.iter().max_by_key(|p| p.get().size).cloned();
+        if let Some(largest) = largest {
+            if largest.get().size as f32 > population / 2.0 {
+                let culture = largest.get().culture.clone();
+                self.0.get_mut().primary_culture = culture;
+            }
+        }
+        let primary = self.0.get().primary_culture.clone();
+        let majority = self
+            .0
+            .get()
+            .pops
+            .iter()
+            .filter(|p| p.get().culture == primary)
+            .fold(0, |acc, p| acc + p.get().size) as f32;
+        let share = majority / population;
+        let minorities = self
+            .0
+            .get()
+            .pops
+            .iter()
+            .filter(|p| p.get().culture != primary)
+            .cloned()
+            .collect::<Vec<_>>();
+        for pop in minorities {
+            let (size, culture, religion) = {
+                let pop = pop.get();
+                (pop.size, pop.culture.clone(), pop.religion.clone())
+            };
+            let rate = ASSIMILATION_RATE * (0.5 + culture_affinity(&culture, &primary)) * share;
+            let amount = ((size as f32 * rate).round() as isize).max(1).min(size);
+            pop.get_mut().size -= amount;
+            self.0.get_mut().add_pops(world, primary.clone(), religion, amount);
+            if pop.get().size <= 0 {
+                DestroyPopCommand(pop.clone()).run(world);
+                world.events.add(Rc::new(PopAssimilatedEvent {
+                    culture,
+                    into: primary.clone(),
+                    settlement: self.0.clone(),
+                }));
+            }
+        }
+    }
+}
+
+pub struct UpdateCultureFeaturesCommand(pub CultureId);
+
+impl Command for UpdateCultureFeaturesCommand {
+    fn run(&self, world: &mut World) {
+        self.0.get_mut().martial_history *= MARTIAL_MEMORY;
+        let settlements = world
+            .iter_storage::<Settlement>()
+            .filter(|s| s.get().primary_culture == self.0)
+            .cloned()
+            .collect::<Vec<_>>();
+        if settlements.is_empty() {
+            return;
+        }
+        let coastal = settlements
+            .iter()
+            .filter(|s| {
+                let s = s.get();
+                s.features.contains(&SettlementFeature::Oceanside) || s.features.contains(&SettlementFeature::Harbor)
+            })
+            .count() as f32
+            / settlements.len() as f32;
+        let martial_history = self.0.get().martial_history;
+        // features are gained past a threshold and only lost well below it
+        for (feature, value, threshold) in [
+            (CultureFeature::Seafaring, coastal, SEAFARING_SHARE),
+            (CultureFeature::Warrior, martial_history, WARRIOR_HISTORY),
+        ] {
+            let has = self.0.get().has_feature(feature);
+            if !has && value >= threshold {
+                self.0.get_mut().features.push(feature);
+            } else if has && value < threshold / 2.0 {
+                self.0.get_mut().features.retain(|f| *f != feature);
+            } else {
+                continue;
+            }
+            world.events.add(Rc::new(CultureFeatureEvent {
+                culture: self.0.clone(),
+                feature,
+                gained: !has,
+            }));
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+// how quickly opinions settle back to their baseline each year
+pub const OPINION_DRIFT: f32 = 0.2;
+pub const NON_AGGRESSION_OPINION: f32 = 15.0;
+pub const ALLIANCE_OPINION: f32 = 40.0;
+// yearly chance of signing a treaty once the conditions are met
+pub const TREATY_CHANCE: f64 = 0.2;
+// how much bigger an overlord must be to demand tribute
+pub const TRIBUTE_RATIO: isize = 3;
+// share of a tributary's treasury paid each year
+pub const TRIBUTE_RATE: f32 = 0.2;
+// years of loyal tribute before a tributary is bound as a vassal
+pub const VASSAL_YEARS: usize = 10;
+// how sour a tributary can be on its overlord and still be bound as a vassal
+pub const VASSAL_OPINION: f32 = -20.0;
+// each further year of tribute makes the tributary this much more resigned to its lot
+pub const VASSAL_RESIGNATION: f32 = 2.0;
+// years a confederacy takes to fold a vassal in completely
+pub const INTEGRATION_YEARS: usize = 20;
+// vassals a chief needs before they are the head of a confederacy
+pub const CONFEDERACY_VASSALS: usize = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TreatyKind {
+    NonAggression,
+    Alliance,
+    Tributary,
+    Vassal,
+}
+
+impl TreatyKind {
+    // tributaries and vassals are bound to an overlord, the rest are between equals
+    pub fn is_subjugation(self) -> bool {
+        self == TreatyKind::Tributary || self == TreatyKind::Vassal
+    }
+}
+
+// for subjugation treaties the first party is the overlord
+pub struct Treaty {
+    pub kind: TreatyKind,
+    pub first: PolityId,
+    pub second: PolityId,
+    pub signed: Date,
+}
+
+impl Treaty {
+    pub fn involves(&self, polity: &PolityId) -> bool {
+        self.first == *polity || self.second == *polity
+    }
+
+    pub fn other(&self, polity: &PolityId) -> PolityId {
+        if self.first == *polity {
+            self.second.clone()
+        } else {
+            self.first.clone()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Diplomacy {
+    pub opinions: HashMap<(PolityId, PolityId), f32>,
+    pub treaties: Vec<Treaty>,
+}
+
+impl Diplomacy {
+    pub fn opinion(&self, of: &PolityId, towards: &PolityId) -> f32 {
+        *self.opinions.get(&(of.clone(), towards.clone())).unwrap_or(&0.0)
+    }
+
+    pub fn add_opinion(&mut self, of: &PolityId, towards: &PolityId, amount: f32) {
+        *self.opinions.entry((of.clone(), towards.clone())).or_insert(0.0) += amount;
+    }
+
+    pub fn treaty_between(&self, a: &PolityId, b: &PolityId) -> Option<&Treaty> {
+        self.treaties.iter().find(|t| t.involves(a) && t.involves(b))
+    }
+
+    pub fn overlord(&self, subject: &PolityId) -> Option<&Treaty> {
+        self.treaties
+            .iter()
+            .find(|t| t.kind.is_subjugation() && t.second == *subject)
+    }
+
+    pub fn vassals(&self, overlord: &PolityId) -> Vec<PolityId> {
+        self.treaties
+            .iter()
+            .filter(|t| t.kind == TreatyKind::Vassal && t.first == *overlord)
+            .map(|t| t.second.clone())
+            .collect()
+    }
+
+    pub fn allies(&self, polity: &PolityId) -> Vec<PolityId> {
+        self.treaties
+            .iter()
+            .filter(|t| t.kind == TreatyKind::Alliance && t.involves(polity))
+            .map(|t| t.other(polity))
+            .collect()
+    }
+
+    // one treaty per pair, a new one replaces whatever was there
+    pub fn sign(&mut self, treaty: Treaty) {
+        self.remove_treaty(&treaty.first, &treaty.second);
+        self.treaties.push(treaty);
+    }
+
+    pub fn remove_treaty(&mut self, a: &PolityId, b: &PolityId) -> Option<Treaty> {
+        let idx = self.treaties.iter().position(|t| t.involves(a) && t.involves(b))?;
+        Some(self.treaties.remove(idx))
+    }
+
+    pub fn forget(&mut self, polity: &PolityId) {
+        self.treaties.retain(|t| !t.involves(polity));
+        self.opinions.retain(|(a, b), _| a != polity && b != polity);
+    }
+}
+
+pub fn baseline_opinion(of: &PolityId, towards: &PolityId, bordering: bool, world: &World) -> f32 {
+    let mut baseline = -10.0;
+    // kindred peoples get along better
+    baseline += culture_affinity(&of.get().primary_culture, &towards.get().primary_culture) * 35.0;
+    if of.get().religion == towards.get().religion {
+        baseline += 10.0;
+    } else if of.get().religion.get().has_doctrine(Doctrine::Proselytizing) {
+        baseline -= 15.0;
+    } else {
+        baseline -= 5.0;
+    }
+    if bordering {
+        baseline -= 10.0;
+    }
+    // a likeable ruler makes friends
+    baseline += towards.factor(world, FactorType::PolityDiplomacy) * 30.0;
+    if world.at_war(of, towards) {
+        baseline -= 100.0;
+    }
+    if let Some(treaty) = world.diplomacy.treaty_between(of, towards) {
+        baseline += match treaty.kind {
+            TreatyKind::NonAggression => 10.0,
+            TreatyKind::Alliance => 30.0,
+            // nobody enjoys paying
+            TreatyKind::Tributary if treaty.second == *of => -20.0,
+            TreatyKind::Vassal if treaty.second == *of => -10.0,
+            _ => 0.0,
+        };
+    }
+    baseline
+}
+
+pub fn diplomacy_tick(world: &World) {
+    world.add_command(Box::new(UpdateOpinionsCommand));
+
+    let settlements = settlements_by_polity(world);
+    let borders = polity_borders(world);
+    let population = |polity: &PolityId| -> isize {
+        settlements
+            .get(polity)
+            .map(|ss| ss.iter().map(|s| s.get().population(world)).sum())
+            .unwrap_or(0)
+    };
+    let diplomacy = &world.diplomacy;
+
+    for treaty in diplomacy.treaties.iter() {
+        let (a, b) = (&treaty.first, &treaty.second);
+        let years = treaty.signed.age(world.date);
+        match treaty.kind {
+            TreatyKind::NonAggression if diplomacy.opinion(a, b).min(diplomacy.opinion(b, a)) < -25.0 => {
+                world.add_command(Box::new(BreakTreatyCommand { a: a.clone(), b: b.clone() }));
+            },
+            TreatyKind::Alliance if diplomacy.opinion(a, b).min(diplomacy.opinion(b, a)) < 0.0 => {
+                world.add_command(Box::new(BreakTreatyCommand { a: a.clone(), b: b.clone() }));
+            },
+            TreatyKind::Tributary | TreatyKind::Vassal
+                if diplomacy.opinion(b, a) < -50.0 && population(b) * 2 > population(a) =>
+            {
+                // the subject feels strong enough to stop paying
+                world.add_command(Box::new(BreakTreatyCommand { a: a.clone(), b: b.clone() }));
+            },
+            TreatyKind::Tributary => {
+                world.add_command(Box::new(PayTributeCommand {
+                    overlord: a.clone(),
+                    tributary: b.clone(),
+                }));
+                if years >= VASSAL_YEARS
+                    && a.get().primary_culture == b.get().primary_culture
+                    && diplomacy.opinion(b, a) + (years - VASSAL_YEARS) as f32 * VASSAL_RESIGNATION >= VASSAL_OPINION
+                {
+                    world.add_command(Box::new(SignTreatyCommand {
+                        kind: TreatyKind::Vassal,
+                        first: a.clone(),
+                        second: b.clone(),
+                    }));
+                }
+            },
+            TreatyKind::Vassal => {
+                world.add_command(Box::new(PayTributeCommand {
+                    overlord: a.clone(),
+                    tributary: b.clone(),
+                }));
+                if a.get().level == PolityLevel::Confederacy
+                    && years >= INTEGRATION_YEARS
+                    && a.get().primary_culture == b.get().primary_culture
+                {
+                    world.add_command(Box::new(UnitePolitiesCommand {
+                        absorber: a.clone(),
+                        absorbed: b.clone(),
+                        level: PolityLevel::Confederacy,
+                    }));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    // chiefs with enough sworn vassals lead a confederacy
+    let mut overlords: HashSet<PolityId> = HashSet::new();
+    for treaty in diplomacy.treaties.iter() {
+        if treaty.kind == TreatyKind::Vassal {
+            overlords.insert(treaty.first.clone());
+        }
+    }
+    for overlord in overlords {
+        let level = overlord.get().level;
+        if (level == PolityLevel::Tribe || level == PolityLevel::Chiefdom)
+            && diplomacy.vassals(&overlord).len() >= CONFEDERACY_VASSALS
+        {
+            world.add_command(Box::new(ChangePolityLevelCommand {
+                polity: overlord.clone(),
+                level: PolityLevel::Confederacy,
+            }));
+        }
+    }
+
+    for (a, neighbors) in borders.iter() {
+        for b in neighbors.iter() {
+            // each pair once
+            if a.num() > b.num() || world.at_war(a, b) {
+                continue;
+            }
+            let opinion = diplomacy.opinion(a, b).min(diplomacy.opinion(b, a));
+            let existing = diplomacy.treaty_between(a, b).map(|t| t.kind);
+            let (strong, weak) = if population(a) >= population(b) { (a, b) } else { (b, a) };
+            // a big neighbor on both sides pushes polities together
+            let shared_threat = borders
+                .get(b)
+                .map(|b_neighbors| {
+                    neighbors.intersection(b_neighbors).any(|c| {
+                        population(c) > (population(a) + population(b)) * PRESSURE_RATIO / 2
+                    })
+                })
+                .unwrap_or(false);
+            let proposal = match existing {
+                None if population(weak) * TRIBUTE_RATIO < population(strong)
+                    && diplomacy.overlord(weak).is_none()
+                    && diplomacy.overlord(strong).is_none() =>
+                {
+                    Some((TreatyKind::Tributary, strong, weak))
+                },
+                None if opinion >= NON_AGGRESSION_OPINION => Some((TreatyKind::NonAggression, a, b)),
+                Some(TreatyKind::NonAggression) if opinion >= ALLIANCE_OPINION || shared_threat => {
+                    Some((TreatyKind::Alliance, a, b))
+                },
+                _ => None,
+            };
+            if let Some((kind, first, second)) = proposal {
+                if individual_event(TREATY_CHANCE) {
+                    world.add_command(Box::new(SignTreatyCommand {
+                        kind,
+                        first: first.clone(),
+                        second: second.clone(),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+pub struct UpdateOpinionsCommand;
+
+impl Command for UpdateOpinionsCommand {
+    fn run(&self, world: &mut World) {
+        let borders = polity_borders(world);
+        // grudges and favors are kept even between polities that no longer touch
+        let mut pairs: HashSet<(PolityId, PolityId)> = world
+            .diplomacy
+            .opinions
+            .keys()
+            .filter(|(a, b)| world.contains(a) && world.contains(b))
+            .cloned()
+            .collect();
+        for (a, neighbors) in borders.iter() {
+            for b in neighbors.iter() {
+                pairs.insert((a.clone(), b.clone()));
+            }
+        }
+        for treaty in world.diplomacy.treaties.iter() {
+            pairs.insert((treaty.first.clone(), treaty.second.clone()));
+            pairs.insert((treaty.second.clone(), treaty.first.clone()));
+        }
+        let mut opinions = HashMap::new();
+        for (a, b) in pairs {
+            let bordering = borders.get(&a).map(|n| n.contains(&b)).unwrap_or(false);
+            let baseline = baseline_opinion(&a, &b, bordering, world);
+            let current = world
+                .diplomacy
+                .opinions
+                .get(&(a.clone(), b.clone()))
+                .copied()
+                .unwrap_or(baseline);
+            opinions.insert((a, b), current + (baseline - current) * OPINION_DRIFT);
+        }
+        world.diplomacy.opinions = opinions;
+    }
+}
+
+pub struct SignTreatyCommand {
+    pub kind: TreatyKind,
+    pub first: PolityId,
+    pub second: PolityId,
+}
+
+impl Command for SignTreatyCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.first) || !world.contains(&self.second) || world.at_war(&self.first, &self.second) {
+            return;
+        }
+        world.diplomacy.sign(Treaty {
+            kind: self.kind,
+            first: self.first.clone(),
+            second: self.second.clone(),
+            signed: world.date,
+        });
+        world.events.add(Rc::new(TreatySignedEvent {
+            kind: self.kind,
+            first: self.first.clone(),
+            second: self.second.clone(),
+        }));
+    }
+}
+
+pub struct BreakTreatyCommand {
+    pub a: PolityId,
+    pub b: PolityId,
+}
+
+impl Command for BreakTreatyCommand {
+    fn run(&self, world: &mut World) {
+        if let Some(treaty) = world.diplomacy.remove_treaty(&self.a, &self.b) {
+            if treaty.kind.is_subjugation() {
+                // overlords do not take kindly to it
+                world.diplomacy.add_opinion(&treaty.first, &treaty.second, -40.0);
+            }
+            world.events.add(Rc::new(TreatyBrokenEvent {
+                kind: treaty.kind,
+                first: treaty.first,
+                second: treaty.second,
+            }));
+        }
+    }
+}
+
+pub struct PayTributeCommand {
+    pub overlord: PolityId,
+    pub tributary: PolityId,
+}
+
+impl Command for PayTributeCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.overlord) || !world.contains(&self.tributary) {
+            return;
+        }
+        let paid = self.tributary.get_mut().treasury.take_fraction(TRIBUTE_RATE);
+        let goods = paid.0.iter().map(|(&good, &amount)| (good, amount)).collect();
+        self.overlord.get_mut().treasury.absorb(paid);
+        world.diplomacy.add_opinion(&self.tributary, &self.overlord, -5.0);
+        world.events.add(Rc::new(TributePaidEvent {
+            overlord: self.overlord.clone(),
+            tributary: self.tributary.clone(),
+            goods,
+        }));
+    }
+}
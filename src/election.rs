@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::thread_rng;
+
+use crate::*;
+
+// standing a candidate gets from each title they already hold
+pub const TITLE_PRESTIGE: f32 = 10.0;
+// voters lean towards themselves and their own family
+pub const SELF_LOYALTY: f32 = 30.0;
+pub const DYNASTY_LOYALTY: f32 = 15.0;
+pub const VOTE_NOISE: f64 = 15.0;
+pub const ELECTION_PRESTIGE: f32 = 10.0;
+
+pub fn candidate_score(candidate: &CharacterId, world: &World) -> f32 {
+    let c = candidate.get();
+    let mut score = c.prestige + TITLE_PRESTIGE * c.titles.len() as f32;
+    score += 50.0 * c.trait_modifier(TraitEffect::Diplomacy);
+    score += 30.0 * c.trait_modifier(TraitEffect::Stewardship);
+    score += 20.0 * c.trait_modifier(TraitEffect::Martial);
+    // nobody wants a boy or a dotard
+    score -= (c.age(world.date) as f32 - 40.0).abs() / 2.0;
+    score
+}
+
+fn can_stand(candidate: &CharacterId, previous: &CharacterId, gender: GenderLaw, world: &World) -> bool {
+    let c = candidate.get();
+    candidate != previous
+        && c.alive()
+        && c.is_adult(world.date)
+        && gender.eligible(c.sex)
+        && !c.titles.iter().any(|t| matches!(t, Title::PolityLeader(_)))
+}
+
+// the old ruler's family and the ruling dynasty's hangers-on
+fn kin_candidates(previous: &CharacterId, dynasty: Option<DynastyId>, gender: GenderLaw, world: &World) -> Vec<CharacterId> {
+    let p = previous.get();
+    let mut kin = p.living_children(None);
+    kin.extend(p.siblings());
+    if let Some(relative) = dynasty.and_then(|d| landless_relative(&d, gender, world)) {
+        kin.push(relative);
+    }
+    kin
+}
+
+fn finalize_candidates(
+    mut candidates: Vec<CharacterId>,
+    previous: &CharacterId,
+    culture: &CultureId,
+    gender: GenderLaw,
+    world: &mut World,
+) -> Vec<CharacterId> {
+    candidates.retain(|c| can_stand(c, previous, gender, world));
+    // under cognatic law women only stand when no man will
+    if gender == GenderLaw::Cognatic && candidates.iter().any(|c| c.get().sex == Sex::Male) {
+        candidates.retain(|c| c.get().sex == Sex::Male);
+    }
+    let mut seen = HashSet::new();
+    candidates.retain(|c| seen.insert(c.clone()));
+    // a newcomer from among the common folk only when nobody else will stand,
+    // so no one is made up just to lose
+    if candidates.is_empty() {
+        let sex = gender.ruler_sex();
+        let newcomer = culture.get().generate_character(sex, positive_isample(8, 40), world, &mut thread_rng());
+        candidates.push(newcomer);
+    }
+    candidates
+}
+
+pub fn polity_candidates(polity: &PolityId, previous: &CharacterId, world: &mut World) -> Vec<CharacterId> {
+    let mut candidates = polity
+        .get()
+        .settlements(world)
+        .iter()
+        .map(|s| s.get().headman.clone())
+        .collect::<Vec<_>>();
+    let gender = polity.get().gender_law;
+    candidates.extend(kin_candidates(previous, previous.get().dynasty.clone(), gender, world));
+    let culture = polity.get().primary_culture.clone();
+    finalize_candidates(candidates, previous, &culture, gender, world)
+}
+
+pub fn settlement_candidates(settlement: &SettlementId, previous: &CharacterId, world: &mut World) -> Vec<CharacterId> {
+    let ruling_dynasty = settlement.get().controller.get().leader.get().dynasty.clone();
+    let gender = settlement.get().controller.get().gender_law;
+    let mut candidates = kin_candidates(previous, ruling_dynasty, gender, world);
+    // notables living there fancy their chances too
+    candidates.extend(
+        world
+            .iter_storage::<Character>()
+            .filter(|c| c.get().alive() && c.get().residence().as_ref() == Some(settlement))
+            .cloned(),
+    );
+    let culture = settlement.get().primary_culture.clone();
+    finalize_candidates(candidates, previous, &culture, gender, world)
+}
+
+// each voter backs one candidate with all their weight, results sorted by votes
+pub fn hold_election(candidates: &[CharacterId], voters: &[(Option<CharacterId>, isize)], world: &World) -> Vec<(CharacterId, isize)> {
+    let scores = candidates
+        .iter()
+        .map(|c| (c.clone(), candidate_score(c, world)))
+        .collect::<Vec<_>>();
+    let mut tally: HashMap<CharacterId, isize> = candidates.iter().map(|c| (c.clone(), 0)).collect();
+    for (voter, weight) in voters.iter() {
+        let voter_dynasty = voter.as_ref().and_then(|v| v.get().dynasty.clone());
+        let choice = scores
+            .iter()
+            .map(|(candidate, score)| {
+                let mut support = *score + dev_mean_sample(VOTE_NOISE, 0.0) as f32;
+                if voter.as_ref() == Some(candidate) {
+                    support += SELF_LOYALTY;
+                }
+                if voter_dynasty.is_some() && candidate.get().dynasty == voter_dynasty {
+                    support += DYNASTY_LOYALTY;
+                }
+                (candidate, support)
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(candidate, _)| candidate.clone());
+        if let Some(choice) = choice {
+            *tally.get_mut(&choice).unwrap() += weight;
+        }
+    }
+    let mut results = tally.into_iter().collect::<Vec<_>>();
+    results.sort_by_key(|(_, votes)| -votes);
+    results
+}
+
+// the headmen vote, each carrying the weight of their settlement
+pub fn elect_polity_leader(polity: &PolityId, previous: &CharacterId, world: &mut World) -> CharacterId {
+    let candidates = polity_candidates(polity, previous, world);
+    let mut voters = polity
+        .get()
+        .settlements(world)
+        .iter()
+        .map(|s| (Some(s.get().headman.clone()), s.get().population(world)))
+        .filter(|(headman, _)| headman.as_ref() != Some(previous))
+        .collect::<Vec<_>>();
+    if voters.is_empty() {
+        voters.push((None, 1));
+    }
+    let results = hold_election(&candidates, &voters, world);
+    let winner = results[0].0.clone();
+    winner.get_mut().prestige += ELECTION_PRESTIGE;
+    world.events.add(Rc::new(ElectionEvent {
+        polity: polity.clone(),
+        results,
+    }));
+    winner
+}
+
+// every household in the settlement has its say
+pub fn elect_headman(settlement: &SettlementId, previous: &CharacterId, world: &mut World) -> CharacterId {
+    let candidates = settlement_candidates(settlement, previous, world);
+    let mut voters = settlement
+        .get()
+        .pops
+        .iter()
+        .map(|p| (None, p.get().size))
+        .collect::<Vec<_>>();
+    if voters.is_empty() {
+        voters.push((None, 1));
+    }
+    let results = hold_election(&candidates, &voters, world);
+    let winner = results[0].0.clone();
+    winner.get_mut().prestige += ELECTION_PRESTIGE;
+    world.events.add(Rc::new(HeadmanElectionEvent {
+        settlement: settlement.clone(),
+        results,
+    }));
+    winner
+}
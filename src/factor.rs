@@ -27,6 +27,12 @@ pub enum FactorType {
     PopPressure,
     PopSize,
     PopHarvest,
+
+    PolityDiplomacy,
+    PolityStewardship,
+    PolityStability,
+    PolityAggression,
+    SettlementStability,
 }
 
 impl FactorField for FactorType {}
@@ -64,7 +70,10 @@ pub enum GameId {
     Culture(usize),
     Settlement(usize),
     Character(usize),
+    Army(usize),
+    Dynasty(usize),
     Religion(usize),
+    Region(usize),
 }
 
 impl FactorSubject for GameId {
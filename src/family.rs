@@ -0,0 +1,526 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::thread_rng;
+
+use crate::*;
+
+pub const ADULT_AGE: usize = 16;
+pub const MAX_MOTHER_AGE: usize = 45;
+pub const MAX_GROOM_AGE: usize = 60;
+// yearly chance an eligible noble finds a match
+pub const MARRIAGE_CHANCE: f64 = 0.3;
+// yearly chance of a birth, shrinking as the family grows
+pub const BIRTH_CHANCE: f64 = 0.3;
+// chance a child picks up each of a parent's congenital features
+pub const FEATURE_INHERITANCE: f64 = 0.35;
+// yearly chance of an old character's health failing
+pub const FRAILTY_AGE: usize = 55;
+pub const FRAILTY_CHANCE: f64 = 0.05;
+
+#[iron_data]
+pub struct Dynasty {
+    pub id: usize,
+    pub name: String,
+    pub founder: CharacterId,
+    pub culture: CultureId,
+}
+
+impl Character {
+    pub fn age(&self, date: Date) -> usize {
+        self.birthday.age(date)
+    }
+
+    pub fn alive(&self) -> bool {
+        self.death.is_none()
+    }
+
+    pub fn is_adult(&self, date: Date) -> bool {
+        self.age(date) >= ADULT_AGE
+    }
+
+    pub fn surname(&self) -> String {
+        self.name.rsplit(' ').next().unwrap_or("").to_owned()
+    }
+
+    // only the families of rulers are worth keeping track of
+    pub fn is_notable(&self) -> bool {
+        !self.titles.is_empty()
+            || self.father.as_ref().map(|f| !f.get().titles.is_empty()).unwrap_or(false)
+            || self.mother.as_ref().map(|m| !m.get().titles.is_empty()).unwrap_or(false)
+    }
+
+    pub fn married(&self) -> bool {
+        self.spouse.as_ref().map(|s| s.get().alive()).unwrap_or(false)
+    }
+
+    // living children, eldest first
+    pub fn living_children(&self, sex: Option<Sex>) -> Vec<CharacterId> {
+        let mut children = self
+            .children
+            .iter()
+            .filter(|c| c.get().alive() && sex.map(|s| c.get().sex == s).unwrap_or(true))
+            .cloned()
+            .collect::<Vec<_>>();
+        children.sort_by_key(|c| c.get().birthday.day);
+        children
+    }
+
+    pub fn siblings(&self) -> Vec<CharacterId> {
+        match &self.father {
+            Some(father) => father
+                .get()
+                .living_children(None)
+                .into_iter()
+                .filter(|c| c.get().id != self.id)
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+pub fn found_dynasty(character: &CharacterId, world: &mut World) -> DynastyId {
+    if let Some(dynasty) = &character.get().dynasty {
+        return dynasty.clone();
+    }
+    let name = character.get().surname();
+    let culture = character.get().culture.clone();
+    let dynasty = world.insert(Dynasty {
+        id: 0,
+        name,
+        founder: character.clone(),
+        culture,
+    });
+    character.get_mut().dynasty = Some(dynasty.clone());
+    dynasty
+}
+
+fn dynasty_members(dynasty: &DynastyId, world: &World) -> Vec<CharacterId> {
+    world
+        .iter_storage::<Character>()
+        .filter(|c| c.get().alive() && c.get().dynasty.as_ref() == Some(dynasty))
+        .cloned()
+        .collect()
+}
+
+// the eldest eligible child, or failing that the eldest line through a dead one
+fn primogeniture_heir(character: &CharacterId, gender: GenderLaw) -> Option<CharacterId> {
+    let mut children = character
+        .get()
+        .children
+        .iter()
+        .filter(|c| gender.eligible(c.get().sex))
+        .cloned()
+        .collect::<Vec<_>>();
+    children.sort_by_key(|c| (gender.precedence(c.get().sex), c.get().birthday.day));
+    for child in children {
+        if child.get().alive() {
+            return Some(child);
+        }
+        if let Some(grandchild) = primogeniture_heir(&child, gender) {
+            return Some(grandchild);
+        }
+    }
+    None
+}
+
+fn seniority_heir(character: &CharacterId, gender: GenderLaw, world: &World) -> Option<CharacterId> {
+    let dynasty = character.get().dynasty.clone()?;
+    dynasty_members(&dynasty, world)
+        .into_iter()
+        .filter(|c| c != character && gender.eligible(c.get().sex) && c.get().is_adult(world.date))
+        .min_by_key(|c| (gender.precedence(c.get().sex), c.get().birthday.day))
+}
+
+pub fn select_heir(character: &CharacterId, law: SuccessorLaw, gender: GenderLaw, world: &World) -> Option<CharacterId> {
+    match law {
+        SuccessorLaw::Primogeniture | SuccessorLaw::Partible => {
+            primogeniture_heir(character, gender).or_else(|| seniority_heir(character, gender, world))
+        },
+        SuccessorLaw::Seniority => seniority_heir(character, gender, world).or_else(|| primogeniture_heir(character, gender)),
+        SuccessorLaw::Election => None,
+    }
+}
+
+// an adult of the ruling family without lands of their own
+pub fn landless_relative(dynasty: &DynastyId, gender: GenderLaw, world: &World) -> Option<CharacterId> {
+    dynasty_members(dynasty, world)
+        .into_iter()
+        .filter(|c| gender.eligible(c.get().sex) && c.get().titles.is_empty() && c.get().is_adult(world.date))
+        .min_by_key(|c| (gender.precedence(c.get().sex), c.get().birthday.day))
+}
+
+pub fn choose_regent(ruler: &CharacterId, world: &mut World) -> CharacterId {
+    let mother = ruler.get().mother.clone().filter(|m| m.get().alive() && m.get().is_adult(world.date));
+    if let Some(mother) = mother {
+        return mother;
+    }
+    let relative = ruler.get().dynasty.as_ref().and_then(|dynasty| {
+        dynasty_members(dynasty, world)
+            .into_iter()
+            .filter(|c| c != ruler && c.get().is_adult(world.date))
+            .min_by_key(|c| c.get().birthday.day)
+    });
+    match relative {
+        Some(relative) => relative,
+        None => {
+            let culture = ruler.get().culture.clone();
+            let sex = culture.get().gender_law.ruler_sex();
+            let regent = culture.get().generate_character(sex, positive_isample(10, 40), world, &mut thread_rng());
+            regent
+        },
+    }
+}
+
+pub fn family_tick(world: &World) {
+    let mut brides: HashMap<CultureId, Vec<CharacterId>> = HashMap::new();
+    let mut grooms: Vec<CharacterId> = Vec::new();
+    for character_id in world.iter_storage::<Character>() {
+        let character = character_id.get();
+        if !character.alive() || !character.is_adult(world.date) {
+            continue;
+        }
+        let age = character.age(world.date);
+        if age >= FRAILTY_AGE && individual_event(FRAILTY_CHANCE) {
+            world.add_command(Box::new(GainTraitCommand {
+                character: character_id.clone(),
+                feature: CharacterFeature::Sickly,
+            }));
+        }
+        if character.married() {
+            let spouse = character.spouse.clone().unwrap();
+            let noble = character.dynasty.is_some() || spouse.get().dynasty.is_some();
+            if noble && character.sex == Sex::Female && age <= MAX_MOTHER_AGE {
+                let chance = BIRTH_CHANCE / (1.0 + character.children.len() as f64 / 2.0);
+                if individual_event(chance) {
+                    world.add_command(Box::new(BirthCommand {
+                        mother: character_id.clone(),
+                        father: spouse,
+                    }));
+                }
+            }
+        } else if character.dynasty.is_some() && character.is_notable() {
+            match character.sex {
+                Sex::Male if age <= MAX_GROOM_AGE => grooms.push(character_id.clone()),
+                Sex::Female if age <= MAX_MOTHER_AGE => brides.entry(character.culture.clone()).or_default().push(character_id.clone()),
+                _ => {},
+            }
+        }
+    }
+    for groom in grooms {
+        if !individual_event(MARRIAGE_CHANCE) {
+            continue;
+        }
+        // prefer a match with another ruling family
+        let dynasty = groom.get().dynasty.clone();
+        let candidates = brides.entry(groom.get().culture.clone()).or_default();
+        let bride = candidates
+            .iter()
+            .position(|b| b.get().dynasty != dynasty)
+            .map(|i| candidates.swap_remove(i));
+        world.add_command(Box::new(MarryCommand {
+            character: groom,
+            spouse: bride,
+        }));
+    }
+    for bride in brides.into_values().flatten() {
+        if individual_event(MARRIAGE_CHANCE) {
+            world.add_command(Box::new(MarryCommand {
+                character: bride,
+                spouse: None,
+            }));
+        }
+    }
+    for polity in world.iter_storage::<Polity>() {
+        let regent = polity.get().regent.clone();
+        if let Some(regent) = regent {
+            let leader = polity.get().leader.clone();
+            if leader.get().is_adult(world.date) || !regent.get().alive() {
+                world.add_command(Box::new(EndRegencyCommand(polity.clone())));
+            }
+        }
+    }
+}
+
+pub struct MarryCommand {
+    pub character: CharacterId,
+    // without a noble match, someone from the common folk
+    pub spouse: Option<CharacterId>,
+}
+
+impl Command for MarryCommand {
+    fn run(&self, world: &mut World) {
+        if self.character.get().married() {
+            return;
+        }
+        let spouse = match &self.spouse {
+            Some(spouse) if !spouse.get().married() => spouse.clone(),
+            _ => {
+                let culture = self.character.get().culture.clone();
+                let sex = match self.character.get().sex {
+                    Sex::Male => Sex::Female,
+                    Sex::Female => Sex::Male,
+                };
+                let age = self.character.get().age(world.date) as isize;
+                let spouse_age = positive_isample(4, age).max(ADULT_AGE as isize);
+                let spouse = culture.get().generate_character(sex, spouse_age, world, &mut thread_rng());
+                spouse
+            },
+        };
+        self.character.get_mut().spouse = Some(spouse.clone());
+        spouse.get_mut().spouse = Some(self.character.clone());
+        if !self.character.get().titles.is_empty() || !spouse.get().titles.is_empty() {
+            world.events.add(Rc::new(MarriageEvent(self.character.clone(), spouse)));
+        }
+    }
+}
+
+pub struct BirthCommand {
+    pub mother: CharacterId,
+    pub father: CharacterId,
+}
+
+impl Command for BirthCommand {
+    fn run(&self, world: &mut World) {
+        let (mother, father) = (self.mother.get(), self.father.get());
+        // a ruling woman's children carry on her line rather than her husband's
+        let ruling_mother = mother.titles.iter().any(|t| matches!(t, Title::PolityLeader(_)));
+        let dynasty = if ruling_mother && mother.dynasty.is_some() {
+            mother.dynasty.clone()
+        } else {
+            father.dynasty.clone().or_else(|| mother.dynasty.clone())
+        };
+        let surname = match &dynasty {
+            Some(dynasty) => dynasty.get().name.clone(),
+            None => father.surname(),
+        };
+        let culture = if ruling_mother { mother.culture.clone() } else { father.culture.clone() };
+        let sex = if individual_event(0.5) { Sex::Male } else { Sex::Female };
+        let mut features = CharacterFeature::random_features(&mut thread_rng());
+        for feature in father.features.iter().chain(mother.features.iter()) {
+            if feature.congenital() && individual_event(FEATURE_INHERITANCE) {
+                if let Some(opposite) = feature.opposite() {
+                    features.remove(&opposite);
+                }
+                features.insert(*feature);
+            }
+        }
+        // congenital health is passed down through the features, not the parents' own
+        let base_health = (father.health - CharacterFeature::health_effect(&father.features)
+            + mother.health
+            - CharacterFeature::health_effect(&mother.features))
+            / 2.0;
+        let health = dev_mean_sample(15.0, base_health as f64) as f32 + CharacterFeature::health_effect(&features);
+        // some of the father's name rubs off
+        let prestige = father.prestige / 4.0;
+        let given_name = culture.get().language.get().generate_given_name(sex);
+        let name = format!("{} {}", given_name, surname);
+        drop((mother, father));
+        let child = world.insert(Character {
+            id: 0,
+            name,
+            birthday: world.date,
+            sex,
+            health,
+            death: None,
+            cause_of_death: None,
+            features,
+            titles: Vec::new(),
+            prestige,
+            culture,
+            dynasty,
+            father: Some(self.father.clone()),
+            mother: Some(self.mother.clone()),
+            spouse: None,
+            children: Vec::new(),
+        });
+        self.mother.get_mut().children.push(child.clone());
+        self.father.get_mut().children.push(child.clone());
+        if !self.father.get().titles.is_empty() || !self.mother.get().titles.is_empty() {
+            world.events.add(Rc::new(CharacterBornEvent(child)));
+        }
+    }
+}
+
+pub struct EndRegencyCommand(pub PolityId);
+
+impl Command for EndRegencyCommand {
+    fn run(&self, world: &mut World) {
+        let regent = match self.0.get_mut().regent.take() {
+            Some(regent) => regent,
+            None => return,
+        };
+        let leader = self.0.get().leader.clone();
+        if !leader.get().is_adult(world.date) {
+            // the regent died before the ruler came of age
+            let regent = choose_regent(&leader, world);
+            self.0.get_mut().regent = Some(regent.clone());
+            world.events.add(Rc::new(RegencyEvent {
+                polity: self.0.clone(),
+                ruler: leader,
+                regent,
+            }));
+            return;
+        }
+        world.events.add(Rc::new(RegencyEndedEvent {
+            polity: self.0.clone(),
+            ruler: leader,
+            regent,
+        }));
+    }
+}
+
+// younger children each carve out a share of the realm
+pub struct PartitionInheritanceCommand {
+    pub polity: PolityId,
+    pub deceased: CharacterId,
+}
+
+impl Command for PartitionInheritanceCommand {
+    fn run(&self, world: &mut World) {
+        let heir = self.polity.get().leader.clone();
+        let gender = self.polity.get().gender_law;
+        let younger_sons = self
+            .deceased
+            .get()
+            .living_children(None)
+            .into_iter()
+            .filter(|s| *s != heir && gender.eligible(s.get().sex))
+            .filter(|s| s.get().titles.iter().all(|t| !matches!(t, Title::PolityLeader(_))))
+            .collect::<Vec<_>>();
+        if younger_sons.is_empty() {
+            return;
+        }
+        let capital = self.polity.get().capital.clone();
+        let settlements = self
+            .polity
+            .get()
+            .settlements(world)
+            .into_iter()
+            .filter(|s| Some(s) != capital.as_ref())
+            .collect::<Vec<_>>();
+        // the eldest keeps the capital and an equal share besides
+        let shares = younger_sons.len() + 1;
+        let culture = self.polity.get().primary_culture.clone();
+        let level = self.polity.get().level;
+        let mut heirs = Vec::new();
+        for (i, son) in younger_sons.into_iter().enumerate() {
+            let share = settlements
+                .iter()
+                .skip(i + 1)
+                .step_by(shares)
+                .cloned()
+                .collect::<Vec<_>>();
+            if share.is_empty() {
+                continue;
+            }
+            let name = culture.get().language.get().generate_toponym();
+            let new_polity = add_polity_with_leader(world, name, culture.clone(), level, son.clone());
+            for settlement in share.iter() {
+                settlement.get_mut().controller = new_polity.clone();
+                for pop in settlement.get().pops.iter() {
+                    pop.get_mut().polity = new_polity.clone();
+                }
+            }
+            new_polity.get_mut().capital = Some(share[0].clone());
+            SetHeadmanCommand {
+                settlement: share[0].clone(),
+                headman: son.clone(),
+            }
+            .run(world);
+            world.diplomacy.add_opinion(&new_polity, &self.polity, 20.0);
+            world.diplomacy.add_opinion(&self.polity, &new_polity, 20.0);
+            heirs.push(new_polity);
+        }
+        if !heirs.is_empty() {
+            world.events.add(Rc::new(PolityPartitionedEvent {
+                polity: self.polity.clone(),
+                heirs,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    struct Family {
+        world: World,
+        rng: StdRng,
+        culture: CultureId,
+    }
+
+    impl Family {
+        fn new() -> Self {
+            let mut world = World {
+                date: Date::from_year(100),
+                ..Default::default()
+            };
+            let mut rng = StdRng::seed_from_u64(1);
+            let culture = add_culture(&mut world, 1, None, &mut rng);
+            Self { world, rng, culture }
+        }
+
+        fn founder(&mut self, age: isize) -> CharacterId {
+            let founder = self.culture.get().generate_character(Sex::Male, age, &mut self.world, &mut self.rng);
+            found_dynasty(&founder, &mut self.world);
+            founder
+        }
+
+        fn child(&mut self, parent: &CharacterId, sex: Sex, age: isize) -> CharacterId {
+            let child = self.culture.get().generate_character(sex, age, &mut self.world, &mut self.rng);
+            child.get_mut().father = Some(parent.clone());
+            child.get_mut().dynasty = parent.get().dynasty.clone();
+            parent.get_mut().children.push(child.clone());
+            child
+        }
+    }
+
+    #[test]
+    fn primogeniture_follows_the_gender_law() {
+        let mut family = Family::new();
+        let ruler = family.founder(60);
+        let daughter = family.child(&ruler, Sex::Female, 30);
+        let son = family.child(&ruler, Sex::Male, 25);
+        let world = &family.world;
+        let law = SuccessorLaw::Primogeniture;
+        assert_eq!(select_heir(&ruler, law, GenderLaw::Absolute, world), Some(daughter.clone()));
+        assert_eq!(select_heir(&ruler, law, GenderLaw::Cognatic, world), Some(son.clone()));
+        assert_eq!(select_heir(&ruler, law, GenderLaw::Agnatic, world), Some(son.clone()));
+        // with the son gone a cognatic line falls back on the daughter, an agnatic one has nobody
+        son.get_mut().death = Some(world.date);
+        assert_eq!(select_heir(&ruler, law, GenderLaw::Cognatic, world), Some(daughter));
+        assert_eq!(select_heir(&ruler, law, GenderLaw::Agnatic, world), None);
+    }
+
+    #[test]
+    fn a_dead_heirs_line_comes_before_younger_siblings() {
+        let mut family = Family::new();
+        let ruler = family.founder(70);
+        let eldest = family.child(&ruler, Sex::Male, 45);
+        let younger = family.child(&ruler, Sex::Male, 40);
+        let grandson = family.child(&eldest, Sex::Male, 10);
+        eldest.get_mut().death = Some(family.world.date);
+        let heir = select_heir(&ruler, SuccessorLaw::Primogeniture, GenderLaw::Agnatic, &family.world);
+        assert_eq!(heir, Some(grandson));
+        assert_ne!(heir, Some(younger));
+    }
+
+    #[test]
+    fn seniority_passes_to_the_eldest_adult_of_the_dynasty() {
+        let mut family = Family::new();
+        let patriarch = family.founder(80);
+        let ruler = family.child(&patriarch, Sex::Male, 50);
+        let brother = family.child(&patriarch, Sex::Male, 45);
+        let son = family.child(&ruler, Sex::Male, 20);
+        family.child(&ruler, Sex::Male, 10);
+        patriarch.get_mut().death = Some(family.world.date);
+        let world = &family.world;
+        assert_eq!(select_heir(&ruler, SuccessorLaw::Seniority, GenderLaw::Agnatic, world), Some(brother.clone()));
+        brother.get_mut().death = Some(world.date);
+        assert_eq!(select_heir(&ruler, SuccessorLaw::Seniority, GenderLaw::Agnatic, world), Some(son));
+        assert_eq!(select_heir(&ruler, SuccessorLaw::Election, GenderLaw::Agnatic, world), None);
+    }
+}
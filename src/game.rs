@@ -6,9 +6,10 @@ use ggez::{
     timer, Context, GameError,
 };
 use lazy_static::lazy_static;
-use rand::{prelude::SliceRandom, random, thread_rng};
+use rand::{prelude::SliceRandom, random, thread_rng, Rng};
 use std::{cell::{Ref, RefCell, RefMut}, collections::{HashMap, HashSet, VecDeque}, fmt::{Debug, Display}, hash::Hash, marker::PhantomData, ops::{Deref, DerefMut}, rc::{Rc, Weak}, slice::Iter, sync::Arc, time::Duration};
 use parking_lot::RwLock;
+use strum::EnumIter;
 pub use GoodType::*;
 
 pub const TILE_SIZE_X: f32 = 16.0;
@@ -72,6 +73,7 @@ impl Coordinate {
         }
     }
 
+    // axial, see hex.rs for the other ways in
     pub fn new(x: isize, y: isize) -> Self {
         Self { x, y }
     }
@@ -81,15 +83,7 @@ impl Coordinate {
     // }
 
     pub fn neighbors(&self) -> Vec<Coordinate> {
-        let mut ns = Vec::new();
-        let directions = vec![(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
-        for (dx, dy) in directions {
-            ns.push(Coordinate {
-                x: self.x + dx,
-                y: self.y + dy,
-            });
-        }
-        ns
+        (0..6).map(|direction| self.neighbor(direction)).collect()
     }
 
     pub fn neighbors_shuffled(&self) -> Vec<Coordinate> {
@@ -111,11 +105,10 @@ impl Coordinate {
     }
 
     pub fn random_local(&self) -> Coordinate {
-        let directions = vec![(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1), (0, 0)];
-        let dir = directions.choose(&mut thread_rng()).unwrap();
-        Coordinate {
-            x: self.x + dir.0,
-            y: self.y + dir.1,
+        // staying put is as likely as any one direction
+        match thread_rng().gen_range(0..7) {
+            6 => *self,
+            direction => self.neighbor(direction),
         }
     }
 
@@ -158,7 +151,7 @@ impl Iterator for CoordinateIter {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
 pub enum Terrain {
     Plains,
     Hills,
@@ -213,9 +206,35 @@ impl Terrain {
             Terrain::Ocean => Color::new(0.1, 0.4, 0.7, 1.0),
         }
     }
+
+    // multiplier on defending armies
+    pub fn defense(self) -> f32 {
+        match self {
+            Terrain::Plains => 1.0,
+            Terrain::Hills => 1.5,
+            Terrain::Mountains => 2.0,
+            Terrain::Desert => 1.0,
+            Terrain::Marsh => 1.25,
+            Terrain::Forest => 1.25,
+            Terrain::Ocean => 1.0,
+        }
+    }
+
+    // days for an army to cross
+    pub fn movement_cost(self) -> f32 {
+        match self {
+            Terrain::Plains => 5.0,
+            Terrain::Hills => 8.0,
+            Terrain::Mountains => 15.0,
+            Terrain::Desert => 10.0,
+            Terrain::Marsh => 12.0,
+            Terrain::Forest => 10.0,
+            Terrain::Ocean => 999.0,
+        }
+    }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
 pub enum Climate {
     Tropical,
     Dry,
@@ -223,7 +242,7 @@ pub enum Climate {
     Cold,
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Copy, Clone, EnumIter)]
 pub enum GoodType {
     Wheat,
     Barley,
@@ -297,6 +316,31 @@ impl GoodType {
         }
     }
 
+    // rough worth in wheat, for weighing mixed stockpiles against each other
+    pub fn value(&self) -> f32 {
+        match *self {
+            Wheat => 1.0,
+            Barley => 0.8,
+            OliveOil => 3.0,
+            Fish => 1.0,
+            Wine => 4.0,
+            Iron => 5.0,
+            Copper => 4.0,
+            Tin => 8.0,
+            Bronze => 10.0,
+            Silver => 30.0,
+            Gold => 100.0,
+            Lead => 2.0,
+            Salt => 3.0,
+            PurpleDye => 50.0,
+            Marble => 5.0,
+            Wood => 0.5,
+            Textiles => 4.0,
+            LuxuryClothes => 20.0,
+            Slaves => 30.0,
+        }
+    }
+
     pub fn consumable_good_catagory(&self) -> Option<ConsumableGoodCatagory> {
         match *self {
             Wheat => Some(ConsumableGoodCatagory::Tier3),
@@ -355,11 +399,12 @@ impl Districts {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, EnumIter)]
 pub enum ProvinceFeature {
     Fertile,
     Infertile,
     NaturalHarbor,
+    Lake,
 }
 
 use SettlementFeature::*;
@@ -374,6 +419,14 @@ pub struct Province {
     pub harvest_month: usize,
     pub controller: Option<PolityId>,
     pub coastal: bool,
+    // where the river running through here flows to
+    pub river: Option<Coordinate>,
+    // ores, salt and the like that can be worked here
+    pub deposits: Vec<GoodType>,
+    pub landmass: Option<RegionId>,
+    pub area: Option<RegionId>,
+    // the zone a sea province is part of, or those a coast looks out on
+    pub sea_zones: Vec<RegionId>,
 }
 
 gen_id!(Province, ProvinceId);
@@ -387,6 +440,28 @@ impl Province {
         total_pop
     }
 
+    pub fn color(&self) -> Color {
+        if self.features.contains(&ProvinceFeature::Lake) {
+            return Color::new(0.2, 0.55, 0.8, 1.0);
+        }
+        let mut color = self.terrain.color();
+        let tint = |color: &mut Color, to: (f32, f32, f32), amount: f32| {
+            color.r += (to.0 - color.r) * amount;
+            color.g += (to.1 - color.g) * amount;
+            color.b += (to.2 - color.b) * amount;
+        };
+        match self.climate {
+            Climate::Cold => tint(&mut color, (0.9, 0.9, 0.95), 0.3),
+            Climate::Dry => tint(&mut color, (0.8, 0.7, 0.4), 0.25),
+            Climate::Tropical => tint(&mut color, (0.1, 0.5, 0.2), 0.2),
+            Climate::Mild => {},
+        }
+        if self.river.is_some() {
+            tint(&mut color, (0.2, 0.5, 0.8), 0.25);
+        }
+        color
+    }
+
     pub fn base_living_target_value(&self) -> f64 {
         let mut total = 0.0;
         total -= match self.terrain {
@@ -424,12 +499,18 @@ impl Province {
                 self.find_one(&mut fmap, Harbor, 0.3);
             }
         }
+        if self.river.is_some() {
+            self.exp_f(&mut fmap, Riverside, 0.6);
+        }
         if self.features.contains(&ProvinceFeature::Fertile) {
             self.exp_f(&mut fmap, Fertile, 0.1);
         }
         if self.features.contains(&ProvinceFeature::Infertile) {
             fmap.add(Infertile, 0.2);
         }
+        for &good in self.deposits.iter() {
+            self.find_one(&mut fmap, Mines(good), 0.2);
+        }
         match self.terrain {
             Terrain::Plains => {
                 self.exp_f(&mut fmap, Hilltop, 0.1);
@@ -465,12 +546,15 @@ impl Province {
         (1.0 + b).powi(count as i32 + 1) - 1.0
     }
 
-    pub fn generate_site(&self, world: &World) -> Site {
+    pub fn generate_site(&self, world: &World, rng: &mut impl Rng) -> Site {
         // let occupied_settlements = self.settlements.iter().map(|sid| sid.get());
         let feature_map  = self.settlement_feature_map(world);
         let mut features: HashSet<SettlementFeature> = HashSet::new();
-        for (&feature, &p) in feature_map.0.iter() {
-            if p < random() {
+        // in a fixed order, so the same rolls land on the same features
+        let mut chances = feature_map.0.into_iter().collect::<Vec<_>>();
+        chances.sort_by_key(|(feature, _)| *feature);
+        for (feature, p) in chances {
+            if p < rng.gen() {
                 features.insert(feature);
                 if feature == Harbor {
                     features.insert(Oceanside);
@@ -489,16 +573,16 @@ impl Province {
         }
     }
 
-    pub fn generate_sites(&self, world: &World, num_sites: usize) -> Vec<Site> {
+    pub fn generate_sites(&self, world: &World, num_sites: usize, rng: &mut impl Rng) -> Vec<Site> {
         let mut candidates = Vec::new();
         for i in 0..num_sites {
-            candidates.push(self.generate_site(world));
+            candidates.push(self.generate_site(world, rng));
         }
         candidates
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter)]
 pub enum PolityLevel {
     Tribe, // one village
     Chiefdom, // a few villages united under a chief
@@ -509,20 +593,112 @@ pub enum PolityLevel {
 }
 
 impl PolityLevel {
-    pub fn leader_title(self) -> &'static str {
+    pub fn leader_title(self, sex: Sex) -> &'static str {
+        match (self, sex) {
+            (PolityLevel::Tribe, Sex::Male) => "Headman",
+            (PolityLevel::Tribe, Sex::Female) => "Headwoman",
+            (PolityLevel::Chiefdom, _) => "Chief",
+            (PolityLevel::Confederacy, _) => "High Chief",
+            (PolityLevel::CityState, _) => "Ruler",
+            (PolityLevel::Kingdom, Sex::Male) => "King",
+            (PolityLevel::Kingdom, Sex::Female) => "Queen",
+            (PolityLevel::Republic, _) => "Consul",
+        }
+    }
+
+    // whether leadership passes down the ruling family
+    pub fn hereditary(self) -> bool {
         match self {
-            PolityLevel::Tribe => "Headman",
-            PolityLevel::Chiefdom => "Chief",
-            PolityLevel::Confederacy => "High Chief",
-            PolityLevel::CityState => "Ruler",
-            PolityLevel::Kingdom => "King",
-            PolityLevel::Republic => "Consul",
+            PolityLevel::Tribe => false,
+            PolityLevel::Chiefdom => true,
+            PolityLevel::Confederacy => false,
+            PolityLevel::CityState => false,
+            PolityLevel::Kingdom => true,
+            PolityLevel::Republic => false,
+        }
+    }
+
+    // share of pop surplus the polity can take
+    pub fn tax_rate(self) -> f32 {
+        match self {
+            PolityLevel::Tribe => 0.02,
+            PolityLevel::Chiefdom => 0.05,
+            PolityLevel::Confederacy => 0.04,
+            PolityLevel::CityState => 0.10,
+            PolityLevel::Kingdom => 0.10,
+            PolityLevel::Republic => 0.08,
+        }
+    }
+
+    // how well the polity holds together, 0 to 1
+    pub fn cohesion(self) -> f32 {
+        match self {
+            PolityLevel::Tribe => 0.9,
+            PolityLevel::Chiefdom => 0.7,
+            PolityLevel::Confederacy => 0.5,
+            PolityLevel::CityState => 0.8,
+            PolityLevel::Kingdom => 0.7,
+            PolityLevel::Republic => 0.75,
+        }
+    }
+}
+
+// who may hold a title: men only, men before women, or either alike
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter)]
+pub enum GenderLaw {
+    Agnatic,
+    Cognatic,
+    Absolute,
+}
+
+impl GenderLaw {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let roll = rng.gen::<f32>();
+        if roll < 0.6 {
+            GenderLaw::Agnatic
+        } else if roll < 0.9 {
+            GenderLaw::Cognatic
+        } else {
+            GenderLaw::Absolute
+        }
+    }
+
+    pub fn eligible(self, sex: Sex) -> bool {
+        self != GenderLaw::Agnatic || sex == Sex::Male
+    }
+
+    // lower comes first in the line of succession
+    pub fn precedence(self, sex: Sex) -> usize {
+        match (self, sex) {
+            (GenderLaw::Cognatic, Sex::Female) => 1,
+            _ => 0,
+        }
+    }
+
+    // the sex of a ruler conjured without a family
+    pub fn ruler_sex(self) -> Sex {
+        self.ruler_sex_with(&mut thread_rng())
+    }
+
+    pub fn ruler_sex_with(self, rng: &mut impl Rng) -> Sex {
+        let female_chance = match self {
+            GenderLaw::Agnatic => 0.0,
+            GenderLaw::Cognatic => 0.1,
+            GenderLaw::Absolute => 0.5,
+        };
+        if rng.gen::<f64>() < female_chance {
+            Sex::Female
+        } else {
+            Sex::Male
         }
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SuccessorLaw {
-    Inheritance(CharacterId),
+    Primogeniture,
+    Seniority,
+    Partible,
     Election,
 }
 
@@ -535,10 +711,37 @@ pub struct Polity {
     pub level: PolityLevel,
     pub leader: CharacterId,
     pub successor_law: SuccessorLaw,
+    pub gender_law: GenderLaw,
+    pub regent: Option<CharacterId>,
+    pub treasury: GoodStorage,
+    pub religion: ReligionId,
 }
 
 gen_id!(Polity, PolityId);
 
+impl Polity {
+    // whoever actually holds power, a regent if the ruler is a minor
+    pub fn ruler(&self) -> CharacterId {
+        self.regent.clone().unwrap_or_else(|| self.leader.clone())
+    }
+
+    pub fn settlements(&self, world: &World) -> Vec<SettlementId> {
+        world
+            .iter_storage::<Settlement>()
+            .filter(|s| s.get().controller.num() == self.id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn provinces(&self, world: &World) -> Vec<ProvinceId> {
+        world
+            .iter_storage::<Province>()
+            .filter(|p| p.get().controller.as_ref().map(|c| c.num()) == Some(self.id))
+            .cloned()
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Site {
     pub features: HashSet<SettlementFeature>,
@@ -550,7 +753,7 @@ pub trait Featured<T> where T: Eq + Hash + Sized {
     fn remove_feature(&mut self, feature: T);
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Copy, Clone)]
 pub enum SettlementFeature {
     Hilltop,
     Riverside,
@@ -562,6 +765,21 @@ pub enum SettlementFeature {
     Infertile,
 }
 
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, EnumIter)]
+pub enum Building {
+    Walls,
+    Temple,
+}
+
+impl Building {
+    pub fn cost(self) -> f32 {
+        match self {
+            Building::Walls => WALLS_COST,
+            Building::Temple => TEMPLE_COST,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SettlementLevel {
     Hamlet,
@@ -589,6 +807,7 @@ pub struct Settlement {
     pub name: String,
     pub pops: Vec<PopId>,
     pub features: HashSet<SettlementFeature>,
+    pub buildings: HashSet<Building>,
     pub primary_culture: CultureId,
     pub province: ProvinceId,
     pub level: SettlementLevel,
@@ -634,14 +853,23 @@ impl Settlement {
 
     pub fn accept_migrants(&mut self, world: &mut World, pop: PopId, amount: isize) {
         // println!("accept_migrants {} {} of {}", self.name, amount, self.population(world));
-        if let Some(dpop) = self.pops.iter().find(|p| p.get().culture == pop.get().culture) {
+        let (culture, religion) = {
+            let pop = pop.get();
+            (pop.culture.clone(), pop.religion.clone())
+        };
+        self.add_pops(world, culture, religion, amount);
+    }
+
+    pub fn add_pops(&mut self, world: &mut World, culture: CultureId, religion: ReligionId, amount: isize) {
+        if let Some(dpop) = self.pops.iter().find(|p| p.get().culture == culture && p.get().religion == religion) {
             dpop.get_mut().size += amount;
         } else {
             let pop_id = world.insert(Pop {
                 id: 0,
                 size: amount,
                 farmed_good: Some(Wheat),
-                culture: pop.get().culture.clone(),
+                culture,
+                religion,
                 settlement: self.id(world).clone(),
                 province: self.province.clone(),
                 satiety: Satiety {
@@ -652,7 +880,9 @@ impl Settlement {
                 owned_goods: GoodStorage(HashMap::new()),
                 migration_status: None,
                 polity: self.controller.clone(),
+                unrest: 0.0,
             });
+            self.pops.push(pop_id);
         }
     }
 }
@@ -764,7 +994,37 @@ impl GoodStorage {
     }
 
     pub fn set(&mut self, good: GoodType, amount: f32) {
-        *self.0.get_mut(&good).unwrap() = amount;
+        self.0.insert(good, amount);
+    }
+
+    pub fn value(&self) -> f32 {
+        self.0.iter().map(|(good, amount)| good.value() * amount).sum()
+    }
+
+    pub fn absorb(&mut self, other: GoodStorage) {
+        for (good, amount) in other.0.into_iter() {
+            self.add(good, amount);
+        }
+    }
+
+    pub fn take_fraction(&mut self, fraction: f32) -> GoodStorage {
+        let mut taken = GoodStorage(HashMap::new());
+        for (good, amount) in self.0.iter_mut() {
+            let part = *amount * fraction;
+            *amount -= part;
+            taken.add(*good, part);
+        }
+        taken
+    }
+
+    // pay out a value spread evenly over everything held, if there is enough
+    pub fn spend_value(&mut self, value: f32) -> bool {
+        let total = self.value();
+        if total < value {
+            return false;
+        }
+        self.take_fraction(value / total);
+        true
     }
 
     // pub fn try_eat_diet(&self, diet: Diet) -> Vec<(GoodType, f32)> {
@@ -897,13 +1157,11 @@ pub struct MainState {
 }
 
 impl MainState {
-    pub fn new(ctx: &mut Context) -> Self {
-        let mut world: World = World::new(ctx);
+    pub fn new(ctx: &mut Context, world: World) -> Self {
         let mut ui_system = UiSystem::default();
         let mut render_context = RenderContext::new(ctx);
 
         ui_system.init(ctx);
-        create_test_world(&mut world);
         render_context.generate_province_meshes(&world, ctx);
         Self {
             world,
@@ -1016,11 +1274,15 @@ impl EventHandler<GameError> for MainState {
                 KeyCode::P => self
                     .render_context
                     .toggle_overlay(ctx, OverlayKind::Population),
+                KeyCode::O => self
+                    .render_context
+                    .toggle_overlay(ctx, OverlayKind::Political),
                 KeyCode::RBracket => self.target_speed = (self.target_speed / 2).max(1),
                 KeyCode::LBracket => self.target_speed = (self.target_speed * 2).min(256),
                 KeyCode::Space => self.target_speed = -self.target_speed,
                 KeyCode::Back => self.ui_system.info_panel_back(),
                 KeyCode::I => self.ui_system.set_info_panel(WorldInfoBuilder),
+                KeyCode::L => self.world.add_command(Box::new(ExportLanguageTreeCommand)),
                 _ => {}
             };
             self.world.events.add(Rc::new(KeyDownEvent {
@@ -18,6 +18,38 @@ pub enum EventKind {
     PopDestroyed,
     CharacterDied,
     PolityLeaderDied,
+    PolityCapitalMoved,
+    PolityDissolved,
+    PolityLevelChanged,
+    PolityUnited,
+    PolityFractured,
+    WarDeclared,
+    AllyJoinedWar,
+    Battle,
+    SettlementOccupied,
+    WarEnded,
+    TreatySigned,
+    TreatyBroken,
+    TributePaid,
+    BuildingConstructed,
+    GiftSent,
+    Revolt,
+    Marriage,
+    CharacterBorn,
+    Regency,
+    RegencyEnded,
+    PolityPartitioned,
+    TraitGained,
+    Election,
+    Epidemic,
+    GenderLawChanged,
+    LanguageDrift,
+    CultureSplit,
+    PopAssimilated,
+    CultureFeatureChanged,
+    Syncretism,
+    StateReligionChanged,
+    ReligiousHead,
 }
 
 impl EventKind {
@@ -150,6 +182,8 @@ impl Default for EventChannels {
         init_eid_channel!(LanguageId);
         init_eid_channel!(PolityId);
         init_eid_channel!(CharacterId);
+        init_eid_channel!(ArmyId);
+        init_eid_channel!(DynastyId);
         Self { event_id_channels: eid_channels }
     }
 }
@@ -157,7 +191,7 @@ impl Default for EventChannels {
 pub struct Events {
     // pub event_command_mapper: EventCommandMapper, //
     pub events:RefCell<Vec<Rc<dyn Event>>>,
-    pub deferred: RefCell<HashMap<usize, Vec<Rc<dyn Event>>>>,
+    pub deferred: RefCell<HashMap<Date, Vec<Rc<dyn Event>>>>,
     pub down_keys: HashSet<KeyCode>,
 }
 
@@ -181,7 +215,7 @@ impl Events {
         self.events.borrow_mut().extend(events.into_iter());
     }
 
-    pub fn add_deferred(&self, event: Rc<dyn Event>, date: usize) {
+    pub fn add_deferred(&self, event: Rc<dyn Event>, date: Date) {
         if self.deferred.borrow().contains_key(&date) {
             self.deferred.borrow_mut().get_mut(&date).unwrap().push(event);
         } else {
@@ -191,7 +225,7 @@ impl Events {
     }
 
     pub fn get_deferred(&self, date: Date) -> Vec<Rc<dyn Event>> {
-        if let Some(events) = self.deferred.borrow_mut().remove(&date.day) {
+        if let Some(events) = self.deferred.borrow_mut().remove(&date) {
             events
         } else {
             Vec::new()
@@ -367,7 +401,7 @@ impl Event for MigrationDoneEvent {
 
     fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
         if let Some(migration_status) = self.0.get().migration_status.as_ref() {
-            if migration_status.date < world.date.day {
+            if migration_status.date < world.date {
                 vec![]
             } else {
                 vec![Box::new(PopMigrateCommand {
@@ -416,9 +450,14 @@ impl Event for CharacterDiedEvent {
                         commands.push(Box::new(SettlementUpdateHeadmanCommand(settlement.clone())));
                     }
                 },
+                Title::ReligiousHead(religion) => {
+                    if religion.get().head.as_ref() == Some(&self.0) {
+                        commands.push(Box::new(AppointReligiousHeadCommand(religion.clone())));
+                    }
+                },
             }
         }
-        commands.push(Box::new(KillCharacterCommand(self.0.clone())));
+        commands.push(Box::new(VacateTitlesCommand(self.0.clone())));
         commands
     }
 
@@ -429,7 +468,8 @@ impl Event for CharacterDiedEvent {
     fn short_description(&self, world: &World) -> String {
         let c = self.0.get();
         // no reanimation!!
-        format!("{} died.  They were {}.", c.title(world), c.birthday.age(c.death.unwrap_or(world.date)))
+        let cause = c.cause_of_death.map(|cause| cause.description()).unwrap_or("");
+        format!("{} died {}.  They were {}.", c.title(world), cause, c.birthday.age(c.death.unwrap_or(world.date)))
     }
 }
 
@@ -468,3 +508,882 @@ impl Event for PopDestroyedEvent {
         self.0.gids()
     }
 }
+
+pub struct PolityCapitalMovedEvent {
+    pub polity: PolityId,
+    pub old_capital: Option<SettlementId>,
+    pub new_capital: SettlementId,
+}
+
+impl Event for PolityCapitalMovedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PolityCapitalMoved
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.polity.gid(), self.new_capital.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let old_name = self.old_capital.as_ref().map(|s| s.get().name.clone()).unwrap_or("nowhere".to_owned());
+        format!("{} moved its capital from {} to {}.", self.polity.get().name, old_name, self.new_capital.get().name)
+    }
+}
+
+pub struct PolityDissolvedEvent(pub PolityId);
+
+impl Event for PolityDissolvedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PolityDissolved
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.0.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has dissolved, holding no more settlements.", self.0.get().name)
+    }
+}
+
+pub struct PolityLevelChangedEvent {
+    pub polity: PolityId,
+    pub from: PolityLevel,
+    pub to: PolityLevel,
+}
+
+impl Event for PolityLevelChangedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PolityLevelChanged
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.polity.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("The {:?} of {} has become a {:?}.", self.from, self.polity.get().name, self.to)
+    }
+}
+
+pub struct PolityUnitedEvent {
+    pub absorber: PolityId,
+    pub absorbed: PolityId,
+}
+
+impl Event for PolityUnitedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PolityUnited
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.absorber.gid(), self.absorbed.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has united with {}.", self.absorbed.get().name, self.absorber.get().name)
+    }
+}
+
+pub struct PolityFracturedEvent {
+    pub polity: PolityId,
+    pub breakaway: PolityId,
+    pub settlements: Vec<SettlementId>,
+}
+
+impl Event for PolityFracturedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PolityFractured
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        let mut subjects = vec![self.polity.gid(), self.breakaway.gid()];
+        subjects.extend(self.settlements.iter().map(|s| s.gid()));
+        subjects
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let settlements = self.settlements.iter().map(|s| s.get().name.clone()).collect::<Vec<_>>();
+        format!(
+            "{} has broken away from {}, taking {}.",
+            self.breakaway.get().name,
+            self.polity.get().name,
+            settlements.join(", ")
+        )
+    }
+}
+
+pub struct WarDeclaredEvent {
+    pub attacker: PolityId,
+    pub defender: PolityId,
+    pub objective: SettlementId,
+}
+
+impl Event for WarDeclaredEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::WarDeclared
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.attacker.gid(), self.defender.gid(), self.objective.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has declared war on {}, marching on {}.", self.attacker.get().name, self.defender.get().name, self.objective.get().name)
+    }
+}
+
+pub struct AllyJoinedWarEvent {
+    pub ally: PolityId,
+    pub defender: PolityId,
+    pub attacker: PolityId,
+}
+
+impl Event for AllyJoinedWarEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::AllyJoinedWar
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.ally.gid(), self.defender.gid(), self.attacker.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has taken up arms beside {} against {}.", self.ally.get().name, self.defender.get().name, self.attacker.get().name)
+    }
+}
+
+pub struct BattleEvent {
+    pub winner: PolityId,
+    pub loser: PolityId,
+    pub province: ProvinceId,
+    pub winner_losses: isize,
+    pub loser_losses: isize,
+}
+
+impl Event for BattleEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Battle
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.winner.gid(), self.loser.gid(), self.province.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "{} defeated {} in battle at {}, losing {} to their {}.",
+            self.winner.get().name,
+            self.loser.get().name,
+            self.province.get().coordinate,
+            self.winner_losses,
+            self.loser_losses,
+        )
+    }
+}
+
+pub struct SettlementOccupiedEvent {
+    pub settlement: SettlementId,
+    pub occupier: PolityId,
+    pub previous: PolityId,
+}
+
+impl Event for SettlementOccupiedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::SettlementOccupied
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.settlement.gid(), self.occupier.gid(), self.previous.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has taken {} from {}.", self.occupier.get().name, self.settlement.get().name, self.previous.get().name)
+    }
+}
+
+pub struct WarEndedEvent {
+    pub attacker: PolityId,
+    pub defender: PolityId,
+    pub victor: Option<PolityId>,
+}
+
+impl Event for WarEndedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::WarEnded
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.attacker.gid(), self.defender.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        match &self.victor {
+            Some(victor) => format!("The war between {} and {} has ended in victory for {}.", self.attacker.get().name, self.defender.get().name, victor.get().name),
+            None => format!("The war between {} and {} has ended in a white peace.", self.attacker.get().name, self.defender.get().name),
+        }
+    }
+}
+
+pub struct TreatySignedEvent {
+    pub kind: TreatyKind,
+    pub first: PolityId,
+    pub second: PolityId,
+}
+
+impl Event for TreatySignedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::TreatySigned
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.first.gid(), self.second.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        match self.kind {
+            TreatyKind::NonAggression => format!("{} and {} have sworn not to attack one another.", self.first.get().name, self.second.get().name),
+            TreatyKind::Alliance => format!("{} and {} have formed an alliance.", self.first.get().name, self.second.get().name),
+            TreatyKind::Tributary => format!("{} has agreed to pay tribute to {}.", self.second.get().name, self.first.get().name),
+            TreatyKind::Vassal => format!("{} has become a vassal of {}.", self.second.get().name, self.first.get().name),
+        }
+    }
+}
+
+pub struct TreatyBrokenEvent {
+    pub kind: TreatyKind,
+    pub first: PolityId,
+    pub second: PolityId,
+}
+
+impl Event for TreatyBrokenEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::TreatyBroken
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.first.gid(), self.second.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        if self.kind.is_subjugation() {
+            format!("{} has thrown off the rule of {}.", self.second.get().name, self.first.get().name)
+        } else {
+            format!("The {:?} treaty between {} and {} has been broken.", self.kind, self.first.get().name, self.second.get().name)
+        }
+    }
+}
+
+pub struct TributePaidEvent {
+    pub overlord: PolityId,
+    pub tributary: PolityId,
+    pub goods: Vec<(GoodType, f32)>,
+}
+
+impl Event for TributePaidEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::TributePaid
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.overlord.gid(), self.tributary.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let goods = self.goods
+            .iter()
+            .map(|(good, amount)| format!("{:.0} {:?}", amount, good))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{} paid tribute of {} to {}.", self.tributary.get().name, goods, self.overlord.get().name)
+    }
+}
+
+pub struct BuildingConstructedEvent {
+    pub polity: PolityId,
+    pub settlement: SettlementId,
+    pub building: Building,
+}
+
+impl Event for BuildingConstructedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::BuildingConstructed
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.polity.gid(), self.settlement.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has built {:?} in {}.", self.polity.get().name, self.building, self.settlement.get().name)
+    }
+}
+
+pub struct GiftSentEvent {
+    pub from: PolityId,
+    pub to: PolityId,
+    pub value: f32,
+}
+
+impl Event for GiftSentEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::GiftSent
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.from.gid(), self.to.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} sent gifts worth {:.0} to {}.", self.from.get().name, self.value, self.to.get().name)
+    }
+}
+
+pub struct RevoltEvent {
+    pub rebels: PolityId,
+    pub former: PolityId,
+    pub settlements: Vec<SettlementId>,
+}
+
+impl Event for RevoltEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Revolt
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        let mut subjects = vec![self.rebels.gid(), self.former.gid()];
+        subjects.extend(self.settlements.iter().map(|s| s.gid()));
+        subjects
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "{} settlements have risen against {} as {}.",
+            self.settlements.len(),
+            self.former.get().name,
+            self.rebels.get().name
+        )
+    }
+}
+
+pub struct MarriageEvent(pub CharacterId, pub CharacterId);
+
+impl Event for MarriageEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Marriage
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.0.gid(), self.1.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has married {}.", self.0.get().title(world), self.1.get().title(world))
+    }
+}
+
+pub struct CharacterBornEvent(pub CharacterId);
+
+impl Event for CharacterBornEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::CharacterBorn
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.0.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let c = self.0.get();
+        let father = c.father.as_ref().map(|f| f.get().title(world)).unwrap_or_default();
+        format!("{} was born to {}.", c.name, father)
+    }
+}
+
+pub struct RegencyEvent {
+    pub polity: PolityId,
+    pub ruler: CharacterId,
+    pub regent: CharacterId,
+}
+
+impl Event for RegencyEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Regency
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.polity.gid(), self.ruler.gid(), self.regent.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "{} rules {} as regent for the young {}.",
+            self.regent.get().name,
+            self.polity.get().name,
+            self.ruler.get().name
+        )
+    }
+}
+
+pub struct RegencyEndedEvent {
+    pub polity: PolityId,
+    pub ruler: CharacterId,
+    pub regent: CharacterId,
+}
+
+impl Event for RegencyEndedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::RegencyEnded
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.polity.gid(), self.ruler.gid(), self.regent.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has come of age and rules {} in their own right.", self.ruler.get().name, self.polity.get().name)
+    }
+}
+
+pub struct PolityPartitionedEvent {
+    pub polity: PolityId,
+    pub heirs: Vec<PolityId>,
+}
+
+impl Event for PolityPartitionedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PolityPartitioned
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        let mut subjects = vec![self.polity.gid()];
+        subjects.extend(self.heirs.iter().map(|h| h.gid()));
+        subjects
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let heirs = self.heirs.iter().map(|h| h.get().name.clone()).collect::<Vec<_>>();
+        format!("{} has been divided among the heirs, creating {}.", self.polity.get().name, heirs.join(", "))
+    }
+}
+
+pub struct TraitGainedEvent {
+    pub character: CharacterId,
+    pub feature: CharacterFeature,
+}
+
+impl Event for TraitGainedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::TraitGained
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.character.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has become {:?}.", self.character.get().title(world), self.feature)
+    }
+}
+
+pub struct ElectionEvent {
+    pub polity: PolityId,
+    pub results: Vec<(CharacterId, isize)>,
+}
+
+impl Event for ElectionEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Election
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        let mut subjects = vec![self.polity.gid()];
+        subjects.extend(self.results.iter().map(|(c, _)| c.gid()));
+        subjects
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let total: isize = self.results.iter().map(|(_, votes)| votes).sum();
+        let breakdown = self
+            .results
+            .iter()
+            .map(|(c, votes)| format!("{} {}", c.get().name, votes))
+            .collect::<Vec<_>>();
+        format!(
+            "{} has been elected to lead {} with {} of {} votes ({}).",
+            self.results[0].0.get().name,
+            self.polity.get().name,
+            self.results[0].1,
+            total,
+            breakdown.join(", ")
+        )
+    }
+}
+
+pub struct HeadmanElectionEvent {
+    pub settlement: SettlementId,
+    pub results: Vec<(CharacterId, isize)>,
+}
+
+impl Event for HeadmanElectionEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Election
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        let mut subjects = vec![self.settlement.gid()];
+        subjects.extend(self.results.iter().map(|(c, _)| c.gid()));
+        subjects
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        let total: isize = self.results.iter().map(|(_, votes)| votes).sum();
+        let breakdown = self
+            .results
+            .iter()
+            .map(|(c, votes)| format!("{} {}", c.get().name, votes))
+            .collect::<Vec<_>>();
+        format!(
+            "{} has been elected headman of {} with {} of {} votes ({}).",
+            self.results[0].0.get().name,
+            self.settlement.get().name,
+            self.results[0].1,
+            total,
+            breakdown.join(", ")
+        )
+    }
+}
+
+pub struct EpidemicEvent {
+    pub settlement: SettlementId,
+    pub deaths: isize,
+}
+
+impl Event for EpidemicEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Epidemic
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.settlement.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("Plague has struck {}, killing {}.", self.settlement.get().name, self.deaths)
+    }
+}
+
+pub struct GenderLawChangedEvent {
+    pub polity: PolityId,
+    pub from: GenderLaw,
+    pub to: GenderLaw,
+}
+
+impl Event for GenderLawChangedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::GenderLawChanged
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.polity.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} has changed its succession from {:?} to {:?}.", self.polity.get().name, self.from, self.to)
+    }
+}
+
+pub struct LanguageDriftEvent {
+    pub language: LanguageId,
+    pub change: SoundChange,
+}
+
+impl Event for LanguageDriftEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::LanguageDrift
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.language.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("In {}, {} has become {}.", self.language.get().name, self.change.from, self.change.to)
+    }
+}
+
+pub struct CultureSplitEvent {
+    pub parent: CultureId,
+    pub culture: CultureId,
+    pub language: LanguageId,
+}
+
+impl Event for CultureSplitEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::CultureSplit
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.parent.gid(), self.culture.gid(), self.language.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "The {} have drifted apart from the {}, speaking {}.",
+            self.culture.get().name,
+            self.parent.get().name,
+            self.language.get().name
+        )
+    }
+}
+
+pub struct PopAssimilatedEvent {
+    pub culture: CultureId,
+    pub into: CultureId,
+    pub settlement: SettlementId,
+}
+
+impl Event for PopAssimilatedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::PopAssimilated
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.culture.gid(), self.into.gid(), self.settlement.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "The {} of {} have become {}.",
+            self.culture.get().name,
+            self.settlement.get().name,
+            self.into.get().name
+        )
+    }
+}
+
+pub struct CultureFeatureEvent {
+    pub culture: CultureId,
+    pub feature: CultureFeature,
+    pub gained: bool,
+}
+
+impl Event for CultureFeatureEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::CultureFeatureChanged
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        self.culture.gids()
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        if self.gained {
+            format!("The {} have become {:?}.", self.culture.get().name, self.feature)
+        } else {
+            format!("The {} are no longer {:?}.", self.culture.get().name, self.feature)
+        }
+    }
+}
+
+pub struct SyncretismEvent {
+    pub religion: ReligionId,
+    pub origins: (ReligionId, ReligionId),
+    pub settlement: SettlementId,
+}
+
+impl Event for SyncretismEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::Syncretism
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.religion.gid(), self.origins.0.gid(), self.origins.1.gid(), self.settlement.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "In {}, the faiths of {} and {} have mingled into {}.",
+            self.settlement.get().name,
+            self.origins.0.get().name,
+            self.origins.1.get().name,
+            self.religion.get().name
+        )
+    }
+}
+
+pub struct StateReligionChangedEvent {
+    pub polity: PolityId,
+    pub from: ReligionId,
+    pub to: ReligionId,
+}
+
+impl Event for StateReligionChangedEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::StateReligionChanged
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.polity.gid(), self.from.gid(), self.to.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!(
+            "{} has forsaken {} for {}.",
+            self.polity.get().name,
+            self.from.get().name,
+            self.to.get().name
+        )
+    }
+}
+
+pub struct ReligiousHeadEvent {
+    pub religion: ReligionId,
+    pub head: CharacterId,
+}
+
+impl Event for ReligiousHeadEvent {
+    fn kind(&self) -> EventKind {
+        EventKind::ReligiousHead
+    }
+
+    fn map_event(&self, world: &World) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+
+    fn subjects(&self) -> Vec<GameId> {
+        vec![self.religion.gid(), self.head.gid()]
+    }
+
+    fn short_description(&self, world: &World) -> String {
+        format!("{} now leads the faithful of {}.", self.head.get().name, self.religion.get().name)
+    }
+}
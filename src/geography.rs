@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+// rough distance between the hearts of neighboring areas and sea zones
+pub const AREA_SPACING: isize = 8;
+pub const SEA_ZONE_SPACING: isize = 14;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+    // land connected without crossing water
+    Landmass,
+    // a named stretch of a landmass
+    Area,
+    SeaZone,
+}
+
+#[iron_data]
+pub struct Region {
+    pub id: usize,
+    pub name: String,
+    pub kind: RegionKind,
+    pub provinces: Vec<ProvinceId>,
+    // the landmass an area lies on
+    pub parent: Option<RegionId>,
+}
+
+impl Region {
+    pub fn population(&self, world: &World) -> isize {
+        self.provinces.iter().map(|p| p.get().population(world)).sum()
+    }
+
+    pub fn settlements(&self) -> Vec<SettlementId> {
+        self.provinces.iter().flat_map(|p| p.get().settlements.clone()).collect()
+    }
+}
+
+// picks seeds in map order, each at least `spacing` away from the others
+fn spaced_seeds(cells: &HashSet<Coordinate>, spacing: isize) -> Vec<Coordinate> {
+    let mut ordered = cells.iter().cloned().collect::<Vec<_>>();
+    ordered.sort_by_key(|c| (c.y, c.x));
+    let mut seeds: Vec<Coordinate> = Vec::new();
+    for coordinate in ordered {
+        if seeds.iter().all(|s| s.dist(coordinate) >= spacing) {
+            seeds.push(coordinate);
+        }
+    }
+    seeds
+}
+
+// splits a connected body into pieces around spaced seeds
+fn partition<C: PathCost>(world: &World, cells: &HashSet<Coordinate>, spacing: isize, cost: &C) -> Vec<Vec<Coordinate>> {
+    let seeds = spaced_seeds(cells, spacing);
+    let field = distance_field(world, &seeds, cost, None);
+    let mut parts = vec![Vec::new(); seeds.len()];
+    let mut ordered = cells.iter().cloned().collect::<Vec<_>>();
+    ordered.sort_by_key(|c| (c.y, c.x));
+    for coordinate in ordered {
+        let nearest = field.nearest(coordinate).unwrap_or(0);
+        parts[nearest].push(coordinate);
+    }
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+fn add_region(world: &mut World, name: String, kind: RegionKind, coordinates: &[Coordinate], parent: Option<RegionId>) -> RegionId {
+    let provinces = coordinates.iter().filter_map(|c| world.get_province_coordinate(*c)).collect();
+    world.insert(Region {
+        id: 0,
+        name,
+        kind,
+        provinces,
+        parent,
+    })
+}
+
+fn sea_movement(world: &World, from: &Province, to: &Province) -> Option<f32> {
+    if to.terrain == Terrain::Ocean {
+        Some(1.0)
+    } else {
+        None
+    }
+}
+
+// neighbors, landmasses, areas and sea zones, worked out once the map is laid down
+pub fn build_geography(world: &mut World, seed: u64) {
+    let mut adjacency: HashMap<ProvinceId, Vec<ProvinceId>> = HashMap::new();
+    let mut land: HashSet<Coordinate> = HashSet::new();
+    let mut sea: HashSet<Coordinate> = HashSet::new();
+    for province in world.iter_storage::<Province>() {
+        let coordinate = province.get().coordinate;
+        let neighbors = coordinate
+            .neighbors()
+            .into_iter()
+            .filter_map(|c| world.get_province_coordinate(c))
+            .collect();
+        adjacency.insert(province.clone(), neighbors);
+        if province.get().terrain == Terrain::Ocean {
+            sea.insert(coordinate);
+        } else {
+            land.insert(coordinate);
+        }
+    }
+    world.adjacency = adjacency;
+
+    // names come from no one's tongue in particular
+    let namer = Language::new(seed, world.date);
+    for landmass in connected_regions(&land) {
+        let mut coordinates = landmass.iter().cloned().collect::<Vec<_>>();
+        coordinates.sort_by_key(|c| (c.y, c.x));
+        let landmass_id = add_region(world, namer.generate_toponym(), RegionKind::Landmass, &coordinates, None);
+        for area in partition(world, &landmass, AREA_SPACING, &LandMovement) {
+            let area_id = add_region(world, namer.generate_toponym(), RegionKind::Area, &area, Some(landmass_id.clone()));
+            for coordinate in area {
+                let province = world.get_province_coordinate(coordinate).unwrap();
+                let mut province = province.get_mut();
+                province.landmass = Some(landmass_id.clone());
+                province.area = Some(area_id.clone());
+            }
+        }
+    }
+
+    for body in connected_regions(&sea) {
+        let lake = body
+            .iter()
+            .all(|c| world.get_province_coordinate(*c).unwrap().get().features.contains(&ProvinceFeature::Lake));
+        let zones = if lake {
+            vec![body.iter().cloned().collect::<Vec<_>>()]
+        } else {
+            partition(world, &body, SEA_ZONE_SPACING, &sea_movement)
+        };
+        for zone in zones {
+            let name = if lake {
+                format!("Lake {}", namer.generate_toponym())
+            } else {
+                format!("{} Sea", namer.generate_toponym())
+            };
+            let zone_id = add_region(world, name, RegionKind::SeaZone, &zone, None);
+            for coordinate in zone {
+                let province = world.get_province_coordinate(coordinate).unwrap();
+                province.get_mut().sea_zones = vec![zone_id.clone()];
+            }
+        }
+    }
+
+    // coasts look out over whichever sea zones they touch
+    for province in world.iter_storage::<Province>() {
+        if province.get().terrain == Terrain::Ocean || !province.get().coastal {
+            continue;
+        }
+        let mut zones: Vec<RegionId> = Vec::new();
+        for neighbor in world.neighbors(province) {
+            if let Some(zone) = neighbor.get().sea_zones.first() {
+                if neighbor.get().terrain == Terrain::Ocean && !zones.contains(zone) {
+                    zones.push(zone.clone());
+                }
+            }
+        }
+        province.get_mut().sea_zones = zones;
+    }
+}
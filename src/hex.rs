@@ -0,0 +1,281 @@
+use std::{collections::HashSet, ops::{Add, Mul, Sub}};
+
+use crate::*;
+
+// the six neighbors in axial steps, going counterclockwise from the east
+pub const HEX_DIRECTIONS: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+// axes to mirror across, each keeping its own cube component
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HexAxis {
+    X,
+    Y,
+    Z,
+}
+
+// coordinates are axial, with the cube's third component derived from the other two
+impl Coordinate {
+    pub fn from_axial(x: isize, y: isize) -> Self {
+        Self::new(x, y)
+    }
+
+    pub fn from_cube(x: isize, y: isize, z: isize) -> Self {
+        debug_assert_eq!(x + y + z, 0, "cube coordinates must sum to zero");
+        Self::new(x, y)
+    }
+
+    pub fn to_cube(&self) -> (isize, isize, isize) {
+        (self.x, self.y, self.z())
+    }
+
+    // column and row on the map, odd rows sitting half a hex to the east
+    pub fn from_offset(col: isize, row: isize) -> Self {
+        Self::new(col - (row - (row & 1)) / 2, row)
+    }
+
+    pub fn to_offset(&self) -> (isize, isize) {
+        (self.x + (self.y - (self.y & 1)) / 2, self.y)
+    }
+
+    pub fn direction(direction: usize) -> Self {
+        let (x, y) = HEX_DIRECTIONS[direction % 6];
+        Self::new(x, y)
+    }
+
+    pub fn neighbor(&self, direction: usize) -> Self {
+        *self + Self::direction(direction)
+    }
+
+    // every hex at exactly `radius`
+    pub fn ring(&self, radius: isize) -> Vec<Coordinate> {
+        if radius <= 0 {
+            return vec![*self];
+        }
+        let mut ring = Vec::new();
+        let mut current = *self + Self::direction(4) * radius;
+        for side in 0..6 {
+            for _ in 0..radius {
+                ring.push(current);
+                current = current.neighbor(side);
+            }
+        }
+        ring
+    }
+
+    // outward from the center ring by ring
+    pub fn spiral(&self, radius: isize) -> Vec<Coordinate> {
+        (0..=radius.max(0)).flat_map(|r| self.ring(r)).collect()
+    }
+
+    // the hexes a straight line passes through, both ends included
+    pub fn line_to(&self, other: Coordinate) -> Vec<Coordinate> {
+        let n = self.dist(other);
+        if n == 0 {
+            return vec![*self];
+        }
+        // walked relative to the start, so the nudge off the edges stays bigger than f32 rounding far from the origin
+        let (dx, dy) = ((other.x - self.x) as f32, (other.y - self.y) as f32);
+        (0..=n)
+            .map(|i| {
+                let t = i as f32 / n as f32;
+                *self + Self::from_cube_round(1e-4 + dx * t, 1e-4 + dy * t)
+            })
+            .collect()
+    }
+
+    // a sixth of a turn about `center` for each step, positive steps going clockwise
+    pub fn rotate(&self, center: Coordinate, steps: isize) -> Self {
+        let (mut x, mut y, mut z) = (*self - center).to_cube();
+        for _ in 0..steps.rem_euclid(6) {
+            let turned = (-y, -z, -x);
+            x = turned.0;
+            y = turned.1;
+            z = turned.2;
+        }
+        Self::from_cube(x, y, z) + center
+    }
+
+    pub fn reflect(&self, center: Coordinate, axis: HexAxis) -> Self {
+        let (x, y, z) = (*self - center).to_cube();
+        let reflected = match axis {
+            HexAxis::X => Self::from_cube(x, z, y),
+            HexAxis::Y => Self::from_cube(z, y, x),
+            HexAxis::Z => Self::from_cube(y, x, z),
+        };
+        reflected + center
+    }
+}
+
+impl Add for Coordinate {
+    type Output = Coordinate;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Coordinate::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Coordinate {
+    type Output = Coordinate;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Coordinate::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<isize> for Coordinate {
+    type Output = Coordinate;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Coordinate::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+// everything reachable from `start` through hexes that pass `inside`
+pub fn flood_fill<F>(start: Coordinate, inside: F) -> HashSet<Coordinate>
+where
+    F: Fn(Coordinate) -> bool,
+{
+    let mut region = HashSet::new();
+    if !inside(start) {
+        return region;
+    }
+    region.insert(start);
+    let mut frontier = vec![start];
+    while let Some(coordinate) = frontier.pop() {
+        for neighbor in coordinate.neighbors_iter() {
+            if !region.contains(&neighbor) && inside(neighbor) {
+                region.insert(neighbor);
+                frontier.push(neighbor);
+            }
+        }
+    }
+    region
+}
+
+// splits a set of hexes into the pieces that touch each other, largest first
+pub fn connected_regions(cells: &HashSet<Coordinate>) -> Vec<HashSet<Coordinate>> {
+    let mut ordered = cells.iter().cloned().collect::<Vec<_>>();
+    ordered.sort_by_key(|c| (c.y, c.x));
+    let mut seen: HashSet<Coordinate> = HashSet::new();
+    let mut regions = Vec::new();
+    for start in ordered {
+        if seen.contains(&start) {
+            continue;
+        }
+        let region = flood_fill(start, |c| cells.contains(&c));
+        seen.extend(region.iter().cloned());
+        regions.push(region);
+    }
+    // stable, so equal sizes stay in map order
+    regions.sort_by_key(|r| std::cmp::Reverse(r.len()));
+    regions
+}
+
+// hexes of the region that touch the outside
+pub fn region_border(region: &HashSet<Coordinate>) -> HashSet<Coordinate> {
+    region
+        .iter()
+        .filter(|c| c.neighbors_iter().any(|n| !region.contains(&n)))
+        .cloned()
+        .collect()
+}
+
+// hexes just outside the region
+pub fn region_outline(region: &HashSet<Coordinate>) -> HashSet<Coordinate> {
+    region
+        .iter()
+        .flat_map(|c| c.neighbors_iter())
+        .filter(|n| !region.contains(n))
+        .collect()
+}
+
+// each inside hex paired with an outside neighbor, one per edge a border line runs along
+pub fn region_edges(region: &HashSet<Coordinate>) -> Vec<(Coordinate, Coordinate)> {
+    let mut edges = region
+        .iter()
+        .flat_map(|c| c.neighbors_iter().filter(|n| !region.contains(n)).map(move |n| (*c, n)))
+        .collect::<Vec<_>>();
+    edges.sort_by_key(|(a, b)| (a.y, a.x, b.y, b.x));
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_has_six_hexes_per_step_all_at_radius() {
+        let center = Coordinate::new(3, -2);
+        assert_eq!(center.ring(0), vec![center]);
+        for radius in 1..5 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|c| c.dist(center) == radius));
+            assert_eq!(ring.iter().collect::<HashSet<_>>().len(), ring.len());
+        }
+    }
+
+    #[test]
+    fn rotate_six_steps_comes_back_around() {
+        let center = Coordinate::new(-4, 7);
+        let start = Coordinate::new(-1, 5);
+        assert_eq!(start.rotate(center, 6), start);
+        assert_eq!(start.rotate(center, 2).rotate(center, -2), start);
+        for steps in 0..6 {
+            assert_eq!(start.rotate(center, steps).dist(center), start.dist(center));
+        }
+        assert_eq!(Coordinate::direction(0).rotate(Coordinate::new(0, 0), 1), Coordinate::direction(5));
+    }
+
+    #[test]
+    fn reflect_twice_is_identity() {
+        let center = Coordinate::new(2, 2);
+        let start = Coordinate::new(5, -1);
+        for axis in [HexAxis::X, HexAxis::Y, HexAxis::Z] {
+            let reflected = start.reflect(center, axis);
+            assert_eq!(reflected.dist(center), start.dist(center));
+            assert_eq!(reflected.reflect(center, axis), start);
+        }
+        assert_eq!(start.reflect(center, HexAxis::X).x, start.x);
+    }
+
+    #[test]
+    fn offset_round_trips() {
+        for row in -5..5 {
+            for col in -5..5 {
+                assert_eq!(Coordinate::from_offset(col, row).to_offset(), (col, row));
+            }
+        }
+        let c = Coordinate::new(-3, 4);
+        let (col, row) = c.to_offset();
+        assert_eq!(Coordinate::from_offset(col, row), c);
+    }
+
+    fn assert_line(a: Coordinate, b: Coordinate) {
+        let line = a.line_to(b);
+        assert_eq!(line.len() as isize, a.dist(b) + 1);
+        assert_eq!(line.first(), Some(&a));
+        assert_eq!(line.last(), Some(&b));
+        assert!(line.windows(2).all(|w| w[0].dist(w[1]) == 1), "{:?}", line);
+    }
+
+    #[test]
+    fn line_to_steps_between_neighbors() {
+        let origin = Coordinate::new(0, 0);
+        assert_eq!(origin.line_to(origin), vec![origin]);
+        assert_line(origin, Coordinate::new(4, -2));
+        assert_line(Coordinate::new(-3, 5), Coordinate::new(2, -1));
+    }
+
+    #[test]
+    fn line_to_is_the_same_far_from_the_origin() {
+        // runs right along an edge halfway, where the nudge decides which side it falls
+        let (a, b) = (Coordinate::new(0, 0), Coordinate::new(2, -1));
+        let near = a.line_to(b);
+        for offset in [Coordinate::new(150, 90), Coordinate::new(-150, -90)] {
+            assert_line(a + offset, b + offset);
+            let far = (a + offset).line_to(b + offset);
+            assert_eq!(far, near.iter().map(|c| *c + offset).collect::<Vec<_>>());
+        }
+    }
+}
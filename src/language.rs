@@ -0,0 +1,642 @@
+use std::{cell::RefCell, collections::HashSet};
+
+use inflector::cases::titlecase::to_title_case;
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
+
+use crate::*;
+
+const FRONT_VOWELS: [&str; 6] = ["e", "i", "ei", "ee", "ae", "ie"];
+const BACK_VOWELS: [&str; 7] = ["a", "o", "u", "au", "ou", "oa", "oi"];
+const CONSONANTS: [&str; 23] = [
+    "b", "c", "d", "f", "g", "h", "j", "k", "l", "m", "n", "p", "r", "s", "t", "v", "w", "z", "ss", "th", "st", "ch", "sh",
+];
+const ONSET_CLUSTERS: [&str; 14] = ["st", "tr", "pr", "kr", "pl", "bl", "gr", "dr", "sk", "sp", "sl", "kl", "br", "thr"];
+const CODA_CLUSTERS: [&str; 8] = ["nt", "st", "rn", "ld", "nd", "rk", "ks", "ls"];
+const PLACE_SUFFIXES: [&str; 10] = ["polis", "ton", "burg", "grad", "heim", "ium", "ora", "is", "ai", "kent"];
+const DEMONYM_SUFFIXES: [&str; 6] = ["ian", "ite", "ene", "ese", "i", "ar"];
+const DYNASTY_SUFFIXES: [&str; 6] = ["id", "son", "ides", "ing", "ar", "en"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stress {
+    Initial,
+    Penultimate,
+    Final,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SyllableTemplate {
+    V,
+    CV,
+    VC,
+    CVC,
+    CCV,
+    CCVC,
+}
+
+impl SyllableTemplate {
+    fn onset(self) -> bool {
+        !matches!(self, SyllableTemplate::V | SyllableTemplate::VC)
+    }
+
+    fn cluster(self) -> bool {
+        matches!(self, SyllableTemplate::CCV | SyllableTemplate::CCVC)
+    }
+
+    fn coda(self) -> bool {
+        matches!(self, SyllableTemplate::VC | SyllableTemplate::CVC | SyllableTemplate::CCVC)
+    }
+
+    // unstressed syllables stay light
+    fn heavy(self) -> bool {
+        self.cluster() || self.coda()
+    }
+}
+
+fn subset(rng: &mut StdRng, list: &[&str], chance: f64) -> Vec<String> {
+    let mut result = list
+        .iter()
+        .filter(|_| rng.gen_bool(chance))
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    if result.is_empty() {
+        result.push(list.choose(rng).unwrap().to_string());
+    }
+    result
+}
+
+fn ends_with_vowel(s: &str) -> bool {
+    s.chars().last().map(|c| "aeiou".contains(c)).unwrap_or(false)
+}
+
+fn starts_with_vowel(s: &str) -> bool {
+    s.chars().next().map(|c| "aeiou".contains(c)).unwrap_or(false)
+}
+
+// glue a suffix on, eliding a clash of vowels
+fn join(stem: &str, suffix: &str) -> String {
+    if ends_with_vowel(stem) && starts_with_vowel(suffix) {
+        format!("{}{}", stem.trim_end_matches(|c| "aeiou".contains(c)), suffix)
+    } else {
+        format!("{}{}", stem, suffix)
+    }
+}
+
+#[iron_data]
+pub struct Language {
+    pub id: usize,
+    pub name: String,
+    pub seed: u64,
+    pub front_vowels: Vec<String>,
+    pub back_vowels: Vec<String>,
+    pub onsets: Vec<String>,
+    pub clusters: Vec<String>,
+    pub codas: Vec<String>,
+    pub templates: Vec<SyllableTemplate>,
+    pub stress: Stress,
+    // whether the vowels of a word must all be front or all back
+    pub harmony: bool,
+    pub male_endings: Vec<String>,
+    pub female_endings: Vec<String>,
+    pub place_suffixes: Vec<String>,
+    pub demonym_suffix: String,
+    pub dynasty_suffix: String,
+    pub rng: RefCell<StdRng>,
+    pub parent: Option<LanguageId>,
+    pub born: Date,
+    // changes since splitting from the parent, oldest first
+    pub sound_changes: Vec<SoundChange>,
+}
+
+impl Language {
+    pub fn new(seed: u64, born: Date) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let consonants = subset(&mut rng, &CONSONANTS, 0.75);
+        let consonant_refs = consonants.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+        let onsets = subset(&mut rng, &consonant_refs, 0.7);
+        let mut codas = subset(&mut rng, &consonant_refs, 0.4);
+        codas.extend(CODA_CLUSTERS.iter().filter(|_| rng.gen_bool(0.15)).map(|s| s.to_string()));
+        let clusters = ONSET_CLUSTERS
+            .iter()
+            .filter(|_| rng.gen_bool(0.3))
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        let front_vowels = subset(&mut rng, &FRONT_VOWELS, 0.7);
+        let back_vowels = subset(&mut rng, &BACK_VOWELS, 0.7);
+
+        let mut templates = vec![SyllableTemplate::CV];
+        for (template, chance) in [
+            (SyllableTemplate::V, 0.5),
+            (SyllableTemplate::VC, 0.3),
+            (SyllableTemplate::CVC, 0.6),
+            (SyllableTemplate::CCV, 0.5),
+            (SyllableTemplate::CCVC, 0.3),
+        ] {
+            if rng.gen_bool(chance) && (!template.cluster() || !clusters.is_empty()) {
+                templates.push(template);
+            }
+        }
+        let stress = *[Stress::Initial, Stress::Penultimate, Stress::Final].choose(&mut rng).unwrap();
+        let harmony = rng.gen_bool(0.3);
+
+        let vowels = front_vowels.iter().chain(back_vowels.iter()).cloned().collect::<Vec<_>>();
+        let male_endings = (0..2)
+            .map(|_| format!("{}{}", vowels.choose(&mut rng).unwrap(), codas.choose(&mut rng).unwrap()))
+            .collect();
+        let female_endings = (0..2)
+            .map(|_| {
+                let vowel = vowels.choose(&mut rng).unwrap();
+                if rng.gen_bool(0.5) {
+                    vowel.clone()
+                } else {
+                    format!("{}{}{}", vowels.choose(&mut rng).unwrap(), onsets.choose(&mut rng).unwrap(), vowel)
+                }
+            })
+            .collect();
+        let mut place_suffixes = PLACE_SUFFIXES
+            .choose_multiple(&mut rng, 2)
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        // and one of the language's own
+        place_suffixes.push(format!("{}{}", onsets.choose(&mut rng).unwrap(), vowels.choose(&mut rng).unwrap()));
+        let demonym_suffix = DEMONYM_SUFFIXES.choose(&mut rng).unwrap().to_string();
+        let dynasty_suffix = DYNASTY_SUFFIXES.choose(&mut rng).unwrap().to_string();
+
+        Self {
+            id: 0,
+            name: "".to_owned(),
+            seed,
+            front_vowels,
+            back_vowels,
+            onsets,
+            clusters,
+            codas,
+            templates,
+            stress,
+            harmony,
+            male_endings,
+            female_endings,
+            place_suffixes,
+            demonym_suffix,
+            dynasty_suffix,
+            rng: RefCell::new(rng),
+            parent: None,
+            born,
+            sound_changes: Vec::new(),
+        }
+    }
+
+    fn syllable(&self, rng: &mut StdRng, template: SyllableTemplate, vowels: &[String]) -> String {
+        let mut syllable = String::new();
+        if template.cluster() {
+            syllable += self.clusters.choose(rng).unwrap();
+        } else if template.onset() {
+            syllable += self.onsets.choose(rng).unwrap();
+        }
+        syllable += vowels.choose(rng).unwrap();
+        if template.coda() {
+            syllable += self.codas.choose(rng).unwrap();
+        }
+        syllable
+    }
+
+    fn word(&self, syllables: usize) -> String {
+        let mut rng = self.rng.borrow_mut();
+        let vowels = if self.harmony {
+            if rng.gen_bool(0.5) {
+                self.front_vowels.clone()
+            } else {
+                self.back_vowels.clone()
+            }
+        } else {
+            self.front_vowels.iter().chain(self.back_vowels.iter()).cloned().collect()
+        };
+        let stressed = match self.stress {
+            Stress::Initial => 0,
+            Stress::Penultimate => syllables.saturating_sub(2),
+            Stress::Final => syllables - 1,
+        };
+        let light = self.templates.iter().filter(|t| !t.heavy()).cloned().collect::<Vec<_>>();
+        let mut word = String::new();
+        for i in 0..syllables {
+            let mut template = if i == stressed {
+                *self.templates.choose(&mut *rng).unwrap()
+            } else {
+                *light.choose(&mut *rng).unwrap()
+            };
+            // no two vowels meeting across syllables
+            if !template.onset() && ends_with_vowel(&word) {
+                template = SyllableTemplate::CV;
+            }
+            word += &self.syllable(&mut rng, template, &vowels);
+        }
+        word
+    }
+
+    fn syllable_count(&self, max: usize) -> usize {
+        self.rng.borrow_mut().gen_range(1..=max.max(1))
+    }
+
+    pub fn generate_name(&self, max_syllables: usize) -> String {
+        let word = self.word(self.syllable_count(max_syllables));
+        to_title_case(word.as_str())
+    }
+
+    pub fn generate_given_name(&self, sex: Sex) -> String {
+        let stem = self.word(self.syllable_count(2));
+        let ending = {
+            let mut rng = self.rng.borrow_mut();
+            match sex {
+                Sex::Male => self.male_endings.choose(&mut *rng).unwrap().clone(),
+                Sex::Female => self.female_endings.choose(&mut *rng).unwrap().clone(),
+            }
+        };
+        to_title_case(join(&stem, &ending).as_str())
+    }
+
+    pub fn generate_dynasty_name(&self) -> String {
+        let stem = self.word(self.syllable_count(2));
+        if self.rng.borrow_mut().gen_bool(0.6) {
+            to_title_case(join(&stem, &self.dynasty_suffix).as_str())
+        } else {
+            to_title_case(stem.as_str())
+        }
+    }
+
+    pub fn generate_toponym(&self) -> String {
+        let stem = self.word(self.syllable_count(2));
+        let suffix = {
+            let mut rng = self.rng.borrow_mut();
+            if rng.gen_bool(0.7) {
+                self.place_suffixes.choose(&mut *rng).cloned()
+            } else {
+                None
+            }
+        };
+        match suffix {
+            Some(suffix) => to_title_case(join(&stem, &suffix).as_str()),
+            None => to_title_case(stem.as_str()),
+        }
+    }
+
+    pub fn demonym(&self, place: &str) -> String {
+        to_title_case(join(&place.to_lowercase(), &self.demonym_suffix).as_str())
+    }
+}
+
+// years between sound changes in a living language
+pub const LANGUAGE_DRIFT_YEARS: isize = 50;
+// settlements further apart than this lose touch with each other's speech
+pub const ISOLATION_DISTANCE: isize = 3;
+pub const MIN_SPLIT_SETTLEMENTS: usize = 3;
+
+const SOUND_SHIFTS: [(&str, &str); 38] = [
+    // vowel shifts
+    ("a", "o"),
+    ("o", "u"),
+    ("e", "i"),
+    ("ei", "i"),
+    ("ou", "u"),
+    ("au", "o"),
+    ("ae", "e"),
+    ("oi", "e"),
+    ("i", "ei"),
+    ("u", "ou"),
+    // lenition
+    ("p", "f"),
+    ("t", "th"),
+    ("c", "ch"),
+    ("k", "h"),
+    ("b", "v"),
+    ("d", "th"),
+    ("g", "h"),
+    ("s", "h"),
+    ("ss", "s"),
+    ("sh", "s"),
+    ("ch", "sh"),
+    ("w", "v"),
+    ("z", "s"),
+    // cluster simplification
+    ("st", "s"),
+    ("tr", "t"),
+    ("pr", "p"),
+    ("kr", "k"),
+    ("pl", "p"),
+    ("bl", "b"),
+    ("gr", "g"),
+    ("dr", "d"),
+    ("sk", "sh"),
+    ("sp", "f"),
+    ("nd", "n"),
+    ("nt", "n"),
+    ("ld", "l"),
+    ("rk", "r"),
+    ("ks", "s"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoundChange {
+    pub from: String,
+    pub to: String,
+}
+
+impl SoundChange {
+    pub fn apply(&self, word: &str) -> String {
+        squeeze(&word.to_lowercase().replace(&self.from, &self.to))
+    }
+
+    pub fn apply_name(&self, name: &str) -> String {
+        to_title_case(self.apply(name).as_str())
+    }
+}
+
+// merged sounds never leave more than a doubled letter behind
+fn squeeze(word: &str) -> String {
+    let mut out = String::new();
+    for c in word.chars() {
+        let mut tail = out.chars().rev();
+        let doubled = tail.next() == Some(c) && (c == 'h' || tail.next() == Some(c));
+        if !doubled {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn apply_all(list: &mut Vec<String>, change: &SoundChange) {
+    let mut changed = Vec::new();
+    for s in list.iter() {
+        let s = change.apply(s);
+        if !s.is_empty() && !changed.contains(&s) {
+            changed.push(s);
+        }
+    }
+    if !changed.is_empty() {
+        *list = changed;
+    }
+}
+
+impl Language {
+    fn inventory(&self) -> Vec<&String> {
+        self.front_vowels
+            .iter()
+            .chain(self.back_vowels.iter())
+            .chain(self.onsets.iter())
+            .chain(self.clusters.iter())
+            .chain(self.codas.iter())
+            .collect()
+    }
+
+    // a sound change that would actually affect this language
+    pub fn random_sound_change(&self) -> Option<SoundChange> {
+        let inventory = self.inventory();
+        let candidates = SOUND_SHIFTS
+            .iter()
+            .filter(|(from, _)| inventory.iter().any(|s| s.contains(from)))
+            .collect::<Vec<_>>();
+        let mut rng = self.rng.borrow_mut();
+        candidates.choose(&mut *rng).map(|(from, to)| SoundChange {
+            from: from.to_string(),
+            to: to.to_string(),
+        })
+    }
+
+    pub fn apply_sound_change(&mut self, change: SoundChange) {
+        for list in [
+            &mut self.front_vowels,
+            &mut self.back_vowels,
+            &mut self.onsets,
+            &mut self.clusters,
+            &mut self.codas,
+            &mut self.male_endings,
+            &mut self.female_endings,
+            &mut self.place_suffixes,
+        ] {
+            apply_all(list, &change);
+        }
+        self.demonym_suffix = change.apply(&self.demonym_suffix);
+        self.dynasty_suffix = change.apply(&self.dynasty_suffix);
+        self.name = change.apply_name(&self.name);
+        self.sound_changes.push(change);
+    }
+
+    pub fn derive(&self, seed: u64, parent: LanguageId, born: Date) -> Language {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut stress = self.stress;
+        if rng.gen_bool(0.3) {
+            stress = *[Stress::Initial, Stress::Penultimate, Stress::Final].choose(&mut rng).unwrap();
+        }
+        let harmony = if rng.gen_bool(0.2) { !self.harmony } else { self.harmony };
+        let changes = rng.gen_range(2..=4);
+        let mut daughter = Language {
+            id: 0,
+            name: self.name.clone(),
+            seed,
+            front_vowels: self.front_vowels.clone(),
+            back_vowels: self.back_vowels.clone(),
+            onsets: self.onsets.clone(),
+            clusters: self.clusters.clone(),
+            codas: self.codas.clone(),
+            templates: self.templates.clone(),
+            stress,
+            harmony,
+            male_endings: self.male_endings.clone(),
+            female_endings: self.female_endings.clone(),
+            place_suffixes: self.place_suffixes.clone(),
+            demonym_suffix: self.demonym_suffix.clone(),
+            dynasty_suffix: self.dynasty_suffix.clone(),
+            rng: RefCell::new(rng),
+            parent: Some(parent),
+            born,
+            sound_changes: Vec::new(),
+        };
+        for _ in 0..changes {
+            if let Some(change) = daughter.random_sound_change() {
+                daughter.apply_sound_change(change);
+            }
+        }
+        daughter
+    }
+
+    pub fn children(&self, world: &World) -> Vec<LanguageId> {
+        world
+            .iter_storage::<Language>()
+            .filter(|l| l.get().parent.as_ref().map(|p| p.num()) == Some(self.id))
+            .cloned()
+            .collect()
+    }
+}
+
+fn write_language_tree(language: &LanguageId, depth: usize, world: &World, out: &mut String) {
+    let l = language.get();
+    let changes = l
+        .sound_changes
+        .iter()
+        .map(|c| format!("{}>{}", c.from, c.to))
+        .collect::<Vec<_>>();
+    out.push_str(&format!(
+        "{}{} ({}) {}\n",
+        "  ".repeat(depth),
+        l.name,
+        l.born.format(DateFormat::Year),
+        changes.join(" ")
+    ));
+    for child in l.children(world) {
+        write_language_tree(&child, depth + 1, world, out);
+    }
+}
+
+// every language family, roots first, with the sound changes that set each apart
+pub fn language_tree(world: &World) -> String {
+    let mut out = String::new();
+    for language in world.iter_storage::<Language>() {
+        if language.get().parent.is_none() {
+            write_language_tree(language, 0, world, &mut out);
+        }
+    }
+    out
+}
+
+// groups of a culture's settlements that are within reach of one another
+pub fn culture_clusters(culture: &CultureId, world: &World) -> Vec<Vec<SettlementId>> {
+    let settlements = world
+        .iter_storage::<Settlement>()
+        .filter(|s| s.get().primary_culture == *culture)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut clusters: Vec<Vec<SettlementId>> = Vec::new();
+    let mut visited = HashSet::new();
+    for start in settlements.iter() {
+        if !visited.insert(start.clone()) {
+            continue;
+        }
+        let mut cluster = vec![start.clone()];
+        let mut i = 0;
+        while i < cluster.len() {
+            let origin = cluster[i].get().province.get().coordinate;
+            for other in settlements.iter() {
+                if !visited.contains(other) && other.get().province.get().coordinate.dist(origin) <= ISOLATION_DISTANCE {
+                    visited.insert(other.clone());
+                    cluster.push(other.clone());
+                }
+            }
+            i += 1;
+        }
+        clusters.push(cluster);
+    }
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    clusters
+}
+
+pub fn language_drift(world: &World) {
+    if world.date.year() % LANGUAGE_DRIFT_YEARS != 0 {
+        return;
+    }
+    for language in world.iter_storage::<Language>() {
+        world.add_command(Box::new(LanguageDriftCommand(language.clone())));
+    }
+    // the largest outlying group of each culture goes its own way
+    for culture in world.iter_storage::<Culture>() {
+        if let Some(cluster) = culture_clusters(culture, world).into_iter().nth(1) {
+            if cluster.len() >= MIN_SPLIT_SETTLEMENTS {
+                world.add_command(Box::new(SplitCultureCommand {
+                    culture: culture.clone(),
+                    settlements: cluster,
+                }));
+            }
+        }
+    }
+}
+
+fn rename_settlements(settlements: &[SettlementId], change: &SoundChange) {
+    for settlement in settlements.iter() {
+        let name = change.apply_name(&settlement.get().name);
+        settlement.get_mut().name = name;
+    }
+}
+
+pub struct LanguageDriftCommand(pub LanguageId);
+
+impl Command for LanguageDriftCommand {
+    fn run(&self, world: &mut World) {
+        let change = match self.0.get().random_sound_change() {
+            Some(change) => change,
+            None => return,
+        };
+        self.0.get_mut().apply_sound_change(change.clone());
+        // place names follow the speech of the people living there
+        let settlements = world
+            .iter_storage::<Settlement>()
+            .filter(|s| s.get().primary_culture.get().language == self.0)
+            .cloned()
+            .collect::<Vec<_>>();
+        rename_settlements(&settlements, &change);
+        world.events.add(Rc::new(LanguageDriftEvent {
+            language: self.0.clone(),
+            change,
+        }));
+    }
+}
+
+pub struct SplitCultureCommand {
+    pub culture: CultureId,
+    pub settlements: Vec<SettlementId>,
+}
+
+impl Command for SplitCultureCommand {
+    fn run(&self, world: &mut World) {
+        let parent_language = self.culture.get().language.clone();
+        let seed = parent_language.get().rng.borrow_mut().gen::<u64>();
+        let mut daughter = parent_language.get().derive(seed, parent_language.clone(), world.date);
+        daughter.name = daughter.generate_name(3);
+        let changes = daughter.sound_changes.clone();
+        let name = daughter.demonym(&daughter.name);
+        let language = world.insert(daughter);
+        let (religion, features, gender_law, martial_history) = {
+            let culture = self.culture.get();
+            (culture.religion.clone(), culture.features.clone(), culture.gender_law, culture.martial_history)
+        };
+        let culture = world.insert(Culture {
+            id: 0,
+            name,
+            religion,
+            language: language.clone(),
+            features,
+            gender_law,
+            parent: Some(self.culture.clone()),
+            founded: world.date,
+            martial_history,
+        });
+        let settlements = self
+            .settlements
+            .iter()
+            .filter(|s| world.contains(*s))
+            .cloned()
+            .collect::<Vec<_>>();
+        for settlement in settlements.iter() {
+            settlement.get_mut().primary_culture = culture.clone();
+            for pop in settlement.get().pops.iter() {
+                if pop.get().culture == self.culture {
+                    pop.get_mut().culture = culture.clone();
+                }
+            }
+        }
+        for change in changes.iter() {
+            rename_settlements(&settlements, change);
+        }
+        world.events.add(Rc::new(CultureSplitEvent {
+            parent: self.culture.clone(),
+            culture,
+            language,
+        }));
+    }
+}
+
+pub struct ExportLanguageTreeCommand;
+
+impl Command for ExportLanguageTreeCommand {
+    fn run(&self, world: &mut World) {
+        if let Err(e) = std::fs::write("language_tree.txt", language_tree(world)) {
+            println!("could not export language tree: {}", e);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use rand::random;
+
+use crate::*;
+
+// yearly loss of vitality once a character is past their prime
+pub const PRIME_AGE: usize = 40;
+pub const AGING_DECAY: f32 = 0.5;
+pub const CHILDHOOD_AGE: usize = 5;
+pub const CHILD_MORTALITY: f64 = 0.05;
+pub const ILLNESS_CHANCE: f64 = 0.02;
+pub const ILLNESS_DAMAGE: f32 = 10.0;
+pub const STARVATION_DAMAGE: f32 = 5.0;
+// base yearly chance of an epidemic in a settlement of EPIDEMIC_POPULATION
+pub const EPIDEMIC_CHANCE: f64 = 0.01;
+pub const EPIDEMIC_POPULATION: f64 = 500.0;
+pub const EPIDEMIC_DAMAGE: f32 = 20.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CauseOfDeath {
+    OldAge,
+    Illness,
+    Childhood,
+    Starvation,
+    Epidemic,
+    Battle,
+}
+
+impl CauseOfDeath {
+    pub fn description(self) -> &'static str {
+        match self {
+            CauseOfDeath::OldAge => "of old age",
+            CauseOfDeath::Illness => "of illness",
+            CauseOfDeath::Childhood => "in childhood",
+            CauseOfDeath::Starvation => "of hunger",
+            CauseOfDeath::Epidemic => "of plague",
+            CauseOfDeath::Battle => "in battle",
+        }
+    }
+}
+
+impl Character {
+    fn seat(&self) -> Option<SettlementId> {
+        self.titles.iter().find_map(|title| match title {
+            Title::SettlementLeader(settlement) => Some(settlement.clone()),
+            Title::PolityLeader(polity) => polity.get().capital.clone(),
+            Title::ReligiousHead(religion) => religion
+                .get()
+                .holy_sites
+                .iter()
+                .find_map(|p| p.get().settlements.first().cloned()),
+        })
+    }
+
+    // where a character lives: their own seat, or the household they belong to
+    pub fn residence(&self) -> Option<SettlementId> {
+        self.seat()
+            .or_else(|| self.spouse.as_ref().and_then(|s| s.get().seat()))
+            .or_else(|| self.father.as_ref().and_then(|f| f.get().seat()))
+            .or_else(|| self.mother.as_ref().and_then(|m| m.get().seat()))
+    }
+}
+
+fn settlement_hungry(settlement: &SettlementId) -> bool {
+    settlement
+        .get()
+        .pops
+        .iter()
+        .any(|p| p.get().satiety.base < 2500.0 * 0.6)
+}
+
+// each character takes stock of their health on their birthday
+pub fn character_lifecycle(world: &World) {
+    let today = world.date.day_of_year();
+    for character_id in world.iter_storage::<Character>() {
+        let character = character_id.get();
+        if !character.alive() || character.birthday.day_of_year() != today || character.birthday == world.date {
+            continue;
+        }
+        let age = character.age(world.date);
+        let mut damage = 0.0;
+        let mut cause = CauseOfDeath::OldAge;
+        if age < CHILDHOOD_AGE && character.father.is_some() && individual_event(CHILD_MORTALITY) {
+            world.add_command(Box::new(KillCharacterCommand(character_id.clone(), CauseOfDeath::Childhood)));
+            continue;
+        }
+        if age > PRIME_AGE {
+            damage += AGING_DECAY;
+        }
+        let illness_chance = if character.has_feature(CharacterFeature::Sickly) {
+            ILLNESS_CHANCE * 2.0
+        } else {
+            ILLNESS_CHANCE
+        };
+        if individual_event(illness_chance) {
+            damage += ILLNESS_DAMAGE;
+            cause = CauseOfDeath::Illness;
+        }
+        if character.residence().map(|s| world.contains(&s) && settlement_hungry(&s)).unwrap_or(false) {
+            damage += STARVATION_DAMAGE;
+            cause = CauseOfDeath::Starvation;
+        }
+        world.add_command(Box::new(DamageHealthCommand {
+            character: character_id.clone(),
+            damage,
+            cause,
+        }));
+    }
+}
+
+pub fn epidemics(world: &World) {
+    let mut residents: HashMap<SettlementId, Vec<CharacterId>> = HashMap::new();
+    for character in world.iter_storage::<Character>() {
+        if character.get().alive() {
+            if let Some(residence) = character.get().residence() {
+                residents.entry(residence).or_default().push(character.clone());
+            }
+        }
+    }
+    for settlement in world.iter_storage::<Settlement>() {
+        // crowding breeds disease
+        let crowding = settlement.get().population(world) as f64 / EPIDEMIC_POPULATION;
+        if individual_event(EPIDEMIC_CHANCE * crowding) {
+            world.add_command(Box::new(EpidemicCommand {
+                settlement: settlement.clone(),
+                residents: residents.remove(settlement).unwrap_or_default(),
+            }));
+        }
+    }
+}
+
+pub struct DamageHealthCommand {
+    pub character: CharacterId,
+    pub damage: f32,
+    pub cause: CauseOfDeath,
+}
+
+impl Command for DamageHealthCommand {
+    fn run(&self, world: &mut World) {
+        let dies = {
+            let mut character = self.character.get_mut();
+            if !character.alive() {
+                return;
+            }
+            character.health -= self.damage;
+            character.age(world.date) as f32 > character.health
+        };
+        if dies {
+            KillCharacterCommand(self.character.clone(), self.cause).run(world);
+        }
+    }
+}
+
+pub struct EpidemicCommand {
+    pub settlement: SettlementId,
+    pub residents: Vec<CharacterId>,
+}
+
+impl Command for EpidemicCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.settlement) {
+            return;
+        }
+        let mut deaths = 0;
+        for pop in self.settlement.get().pops.iter() {
+            let size = pop.get().size;
+            let dead = (size as f64 * dev_mean_sample(0.03, 0.1).max(0.0)) as isize;
+            deaths += pop.get_mut().die(dead);
+        }
+        for resident in self.residents.iter() {
+            if individual_event(0.3) {
+                DamageHealthCommand {
+                    character: resident.clone(),
+                    damage: EPIDEMIC_DAMAGE * random::<f32>(),
+                    cause: CauseOfDeath::Epidemic,
+                }
+                .run(world);
+            }
+        }
+        world.events.add(Rc::new(EpidemicEvent {
+            settlement: self.settlement.clone(),
+            deaths,
+        }));
+    }
+}
+
+// titles are only given up once the succession has been settled
+pub struct VacateTitlesCommand(pub CharacterId);
+
+impl Command for VacateTitlesCommand {
+    fn run(&self, world: &mut World) {
+        self.0.get_mut().titles.clear();
+    }
+}
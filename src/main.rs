@@ -2,6 +2,8 @@
 #![feature(trace_macros)]
 #![allow(unused_variables)]
 #![allow(unused_imports)]
+// ids hash by their immutable number, never by the RefCell contents
+#![allow(clippy::mutable_key_type)]
 
 use ggez::{
     conf::{WindowMode, WindowSetup},
@@ -25,6 +27,24 @@ pub mod agent;
 pub mod formula;
 pub mod factor;
 pub mod log;
+pub mod territory;
+pub mod polity;
+pub mod warfare;
+pub mod diplomacy;
+pub mod treasury;
+pub mod unrest;
+pub mod family;
+pub mod election;
+pub mod lifecycle;
+pub mod language;
+pub mod culture;
+pub mod religion;
+pub mod config;
+pub mod scenario;
+pub mod pathfinding;
+pub mod hex;
+pub mod geography;
+pub mod calendar;
 
 // I'm a bad boy
 pub use commands::*;
@@ -44,12 +64,48 @@ pub use agent::*;
 pub use formula::*;
 pub use factor::*;
 pub use log::*;
+pub use territory::*;
+pub use polity::*;
+pub use warfare::*;
+pub use diplomacy::*;
+pub use treasury::*;
+pub use unrest::*;
+pub use family::*;
+pub use election::*;
+pub use lifecycle::*;
+pub use language::*;
+pub use culture::*;
+pub use religion::*;
+pub use config::*;
+pub use scenario::*;
+pub use pathfinding::*;
+pub use hex::*;
+pub use geography::*;
+pub use calendar::*;
 
 fn main() {
     let cb = ContextBuilder::new("iron-age", "ristew")
         .window_setup(WindowSetup::default().vsync(false).title("iron-age"))
         .window_mode(WindowMode::default().dimensions(1150.0, 750.0));
     let (mut ctx, evt_loop) = cb.build().unwrap();
-    let game = game::MainState::new(&mut ctx);
+    // the first argument is either a scenario file or the name of a worldgen preset
+    let arg = std::env::args().nth(1).unwrap_or_else(|| "inland_sea".to_owned());
+    let mut world = World::new(&mut ctx);
+    if std::path::Path::new(&arg).is_file() {
+        match load_scenario(&mut world, &arg) {
+            Ok(name) => println!("starting scenario {}", name),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        }
+    } else {
+        let config = WorldGenConfig::load(WORLDGEN_PATH, &arg).unwrap_or_else(|e| {
+            println!("{}, using the default world", e);
+            WorldGenConfig::default()
+        });
+        create_test_world(&mut world, &config);
+    }
+    let game = game::MainState::new(&mut ctx, world);
     event::run(ctx, evt_loop, game);
 }
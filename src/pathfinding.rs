@@ -0,0 +1,360 @@
+use std::{cmp::Ordering, collections::{BinaryHeap, HashMap}};
+
+use crate::*;
+
+// days at sea per province, ships being quicker than feet
+pub const SEA_MOVEMENT_COST: f32 = 3.0;
+
+// what it takes to step from one province into its neighbor, None where it can't be entered
+pub trait PathCost {
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32>;
+
+    // the least any step can cost, used to guide the search toward its goal
+    fn min_step(&self) -> f32 {
+        0.0
+    }
+}
+
+impl<F> PathCost for F
+where
+    F: Fn(&World, &Province, &Province) -> Option<f32>,
+{
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32> {
+        self(world, from, to)
+    }
+}
+
+// on foot, over land only
+pub struct LandMovement;
+
+impl PathCost for LandMovement {
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32> {
+        match to.terrain {
+            Terrain::Ocean => None,
+            terrain => Some(terrain.movement_cost()),
+        }
+    }
+
+    fn min_step(&self) -> f32 {
+        Terrain::Plains.movement_cost()
+    }
+}
+
+// by boat, putting out and landing on coasts
+pub struct SeaMovement;
+
+impl PathCost for SeaMovement {
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32> {
+        let at_sea = from.terrain == Terrain::Ocean;
+        match to.terrain {
+            Terrain::Ocean if at_sea || from.coastal => Some(SEA_MOVEMENT_COST),
+            terrain if at_sea && to.coastal => Some(terrain.movement_cost()),
+            _ => None,
+        }
+    }
+
+    fn min_step(&self) -> f32 {
+        SEA_MOVEMENT_COST
+    }
+}
+
+// walking where possible, sailing where not
+pub struct LandAndSea;
+
+impl PathCost for LandAndSea {
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32> {
+        if from.terrain != Terrain::Ocean && to.terrain != Terrain::Ocean {
+            LandMovement.step(world, from, to)
+        } else {
+            SeaMovement.step(world, from, to)
+        }
+    }
+
+    fn min_step(&self) -> f32 {
+        SEA_MOVEMENT_COST.min(LandMovement.min_step())
+    }
+}
+
+// crossing other polities' land costs extra, or is impossible when `foreign` is None
+pub struct Bordered<C: PathCost> {
+    pub inner: C,
+    pub polity: PolityId,
+    pub foreign: Option<f32>,
+}
+
+impl<C: PathCost> PathCost for Bordered<C> {
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32> {
+        let cost = self.inner.step(world, from, to)?;
+        match &to.controller {
+            Some(controller) if *controller != self.polity => self.foreign.map(|foreign| cost + foreign),
+            _ => Some(cost),
+        }
+    }
+
+    fn min_step(&self) -> f32 {
+        self.inner.min_step()
+    }
+}
+
+// extra cost for entering provinces thought to be dangerous
+pub struct Dangerous<C: PathCost> {
+    pub inner: C,
+    pub danger: HashMap<Coordinate, f32>,
+}
+
+impl<C: PathCost> PathCost for Dangerous<C> {
+    fn step(&self, world: &World, from: &Province, to: &Province) -> Option<f32> {
+        let cost = self.inner.step(world, from, to)?;
+        Some(cost + self.danger.get(&to.coordinate).cloned().unwrap_or(0.0))
+    }
+
+    fn min_step(&self) -> f32 {
+        self.inner.min_step()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Path {
+    // from the start to the goal, both included
+    pub steps: Vec<Coordinate>,
+    pub cost: f32,
+}
+
+impl Path {
+    pub fn next(&self) -> Option<Coordinate> {
+        self.steps.get(1).cloned()
+    }
+}
+
+// cheapest first, ties broken by position so searches come out the same every time
+struct Visit {
+    priority: f32,
+    cost: f32,
+    coordinate: Coordinate,
+    source: usize,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| (other.coordinate.y, other.coordinate.x).cmp(&(self.coordinate.y, self.coordinate.x)))
+            .then_with(|| other.source.cmp(&self.source))
+    }
+}
+
+// the neighbors that can be stepped into, and what each costs
+fn steps<C: PathCost>(world: &World, cost: &C, from: Coordinate) -> Vec<(Coordinate, f32)> {
+    let from_id = match world.get_province_coordinate(from) {
+        Some(from_id) => from_id,
+        None => return Vec::new(),
+    };
+    let from = from_id.get();
+    world
+        .neighbors(&from_id)
+        .iter()
+        .filter_map(|to| {
+            let to = to.get();
+            cost.step(world, &from, &to).map(|step| (to.coordinate, step))
+        })
+        .collect()
+}
+
+// A* from one province to another
+pub fn find_path<C: PathCost>(world: &World, from: Coordinate, to: Coordinate, cost: &C) -> Option<Path> {
+    let heuristic = |c: Coordinate| c.dist(to) as f32 * cost.min_step();
+    let mut costs: HashMap<Coordinate, f32> = HashMap::new();
+    let mut came_from: HashMap<Coordinate, Coordinate> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    costs.insert(from, 0.0);
+    frontier.push(Visit { priority: heuristic(from), cost: 0.0, coordinate: from, source: 0 });
+    while let Some(Visit { cost: so_far, coordinate, .. }) = frontier.pop() {
+        if coordinate == to {
+            let mut steps = vec![to];
+            while let Some(previous) = came_from.get(steps.last().unwrap()) {
+                steps.push(*previous);
+            }
+            steps.reverse();
+            return Some(Path { steps, cost: so_far });
+        }
+        if costs.get(&coordinate).is_some_and(|c| so_far > *c) {
+            continue;
+        }
+        for (neighbor, step) in steps(world, cost, coordinate) {
+            let next = so_far + step;
+            if !costs.get(&neighbor).is_some_and(|c| next >= *c) {
+                costs.insert(neighbor, next);
+                came_from.insert(neighbor, coordinate);
+                frontier.push(Visit { priority: next + heuristic(neighbor), cost: next, coordinate: neighbor, source: 0 });
+            }
+        }
+    }
+    None
+}
+
+// cost to reach every province from whichever source is cheapest, for things like
+// the distance to the nearest market
+pub struct DistanceField {
+    pub sources: Vec<Coordinate>,
+    reached: HashMap<Coordinate, (f32, usize)>,
+}
+
+impl DistanceField {
+    pub fn cost(&self, coordinate: Coordinate) -> Option<f32> {
+        self.reached.get(&coordinate).map(|(cost, _)| *cost)
+    }
+
+    // index into `sources` of the nearest one
+    pub fn nearest(&self, coordinate: Coordinate) -> Option<usize> {
+        self.reached.get(&coordinate).map(|(_, source)| *source)
+    }
+
+    pub fn reached(&self) -> impl Iterator<Item = (&Coordinate, f32)> {
+        self.reached.iter().map(|(c, (cost, _))| (c, *cost))
+    }
+}
+
+// Dijkstra out from all the sources at once, stopping past `max_cost` if given
+pub fn distance_field<C: PathCost>(world: &World, sources: &[Coordinate], cost: &C, max_cost: Option<f32>) -> DistanceField {
+    let mut reached: HashMap<Coordinate, (f32, usize)> = HashMap::new();
+    let mut costs: HashMap<Coordinate, f32> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    for (i, source) in sources.iter().enumerate() {
+        costs.insert(*source, 0.0);
+        frontier.push(Visit { priority: 0.0, cost: 0.0, coordinate: *source, source: i });
+    }
+    while let Some(Visit { cost: so_far, coordinate, source, .. }) = frontier.pop() {
+        if reached.contains_key(&coordinate) {
+            continue;
+        }
+        reached.insert(coordinate, (so_far, source));
+        for (neighbor, step) in steps(world, cost, coordinate) {
+            if reached.contains_key(&neighbor) {
+                continue;
+            }
+            let next = so_far + step;
+            if max_cost.is_some_and(|max| next > max) {
+                continue;
+            }
+            if !costs.get(&neighbor).is_some_and(|c| next >= *c) {
+                costs.insert(neighbor, next);
+                frontier.push(Visit { priority: next, cost: next, coordinate: neighbor, source });
+            }
+        }
+    }
+    DistanceField {
+        sources: sources.to_vec(),
+        reached,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    // plains with a mountain in the way and a strait of sea below
+    const MAP: [&str; 4] = ["..M..", "..h..", "~~~~~", "....."];
+
+    fn tiny_world() -> World {
+        let mut world = World::default();
+        for (row, line) in MAP.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                let terrain = match c {
+                    '~' => Terrain::Ocean,
+                    'M' => Terrain::Mountains,
+                    'h' => Terrain::Hills,
+                    _ => Terrain::Plains,
+                };
+                world.insert_province(Province {
+                    id: 0,
+                    terrain,
+                    climate: Climate::Mild,
+                    coordinate: Coordinate::from_offset(col as isize, row as isize),
+                    harvest_month: 8,
+                    settlements: Vec::new(),
+                    features: HashSet::new(),
+                    controller: None,
+                    coastal: false,
+                    river: None,
+                    deposits: Vec::new(),
+                    landmass: None,
+                    area: None,
+                    sea_zones: Vec::new(),
+                });
+            }
+        }
+        mark_coasts(&world);
+        build_geography(&mut world, 0);
+        world
+    }
+
+    fn at(col: isize, row: isize) -> Coordinate {
+        Coordinate::from_offset(col, row)
+    }
+
+    #[test]
+    fn find_path_goes_around_the_mountain() {
+        let world = tiny_world();
+        let path = find_path(&world, at(1, 0), at(3, 0), &LandMovement).unwrap();
+        assert_eq!(path.steps.first(), Some(&at(1, 0)));
+        assert_eq!(path.steps.last(), Some(&at(3, 0)));
+        assert!(!path.steps.contains(&at(2, 0)));
+        assert!(path.steps.windows(2).all(|w| w[0].dist(w[1]) == 1));
+        let plains = Terrain::Plains.movement_cost();
+        assert_eq!(path.cost, plains + Terrain::Hills.movement_cost() + plains);
+        assert_eq!(path.next(), Some(path.steps[1]));
+    }
+
+    #[test]
+    fn find_path_only_crosses_water_by_boat() {
+        let world = tiny_world();
+        assert!(find_path(&world, at(1, 0), at(1, 3), &LandMovement).is_none());
+        let path = find_path(&world, at(1, 1), at(1, 3), &LandAndSea).unwrap();
+        assert_eq!(path.steps.len(), 3);
+        assert_eq!(path.cost, SEA_MOVEMENT_COST + Terrain::Plains.movement_cost());
+        let here = find_path(&world, at(0, 0), at(0, 0), &LandMovement).unwrap();
+        assert_eq!((here.steps, here.cost), (vec![at(0, 0)], 0.0));
+    }
+
+    #[test]
+    fn distance_field_matches_find_path() {
+        let world = tiny_world();
+        let field = distance_field(&world, &[at(1, 0)], &LandMovement, None);
+        for (coordinate, cost) in field.reached() {
+            assert_eq!(Some(cost), find_path(&world, at(1, 0), *coordinate, &LandMovement).map(|p| p.cost));
+        }
+        assert_eq!(field.cost(at(1, 3)), None);
+        assert_eq!(field.reached().count(), 10);
+    }
+
+    #[test]
+    fn distance_field_tracks_the_nearest_source_and_stops_at_max_cost() {
+        let world = tiny_world();
+        let sources = [at(0, 0), at(4, 0)];
+        let field = distance_field(&world, &sources, &LandMovement, None);
+        assert_eq!(field.nearest(at(1, 0)), Some(0));
+        assert_eq!(field.nearest(at(3, 1)), Some(1));
+        assert_eq!(field.cost(at(4, 0)), Some(0.0));
+        let near = distance_field(&world, &sources, &LandMovement, Some(Terrain::Plains.movement_cost()));
+        assert_eq!(near.cost(at(1, 0)), Some(Terrain::Plains.movement_cost()));
+        assert_eq!(near.cost(at(2, 0)), None);
+        assert_eq!(near.cost(at(2, 1)), None);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{cmp::Reverse, collections::{HashMap, HashSet}};
+
+use rand::thread_rng;
+
+use crate::*;
+
+// settlements needed before a tribe is really a chiefdom
+pub const CHIEFDOM_SETTLEMENTS: usize = 3;
+// settlements needed to consolidate into a kingdom
+pub const KINGDOM_SETTLEMENTS: usize = 8;
+// capital population needed for a city to dominate its polity
+pub const CITY_STATE_POPULATION: isize = 400;
+// how much bigger a neighbor has to be before smaller polities band together
+pub const PRESSURE_RATIO: isize = 3;
+// yearly chance of neighboring tribes uniting, scaled by cohesion
+pub const UNION_CHANCE: f64 = 0.02;
+// yearly chance of threatened polities forming a confederacy
+pub const CONFEDERATION_CHANCE: f64 = 0.1;
+// yearly chance of outlying settlements breaking away, scaled by size and by how loose the polity is
+pub const FRACTURE_CHANCE: f64 = 0.05;
+
+pub fn level_successor_law(level: PolityLevel) -> SuccessorLaw {
+    match level {
+        PolityLevel::Kingdom => SuccessorLaw::Primogeniture,
+        PolityLevel::Chiefdom if individual_event(0.5) => SuccessorLaw::Partible,
+        PolityLevel::Chiefdom => SuccessorLaw::Seniority,
+        _ => SuccessorLaw::Election,
+    }
+}
+
+pub fn settlements_by_polity(world: &World) -> HashMap<PolityId, Vec<SettlementId>> {
+    let mut result: HashMap<PolityId, Vec<SettlementId>> = HashMap::new();
+    for settlement in world.iter_storage::<Settlement>() {
+        result
+            .entry(settlement.get().controller.clone())
+            .or_default()
+            .push(settlement.clone());
+    }
+    result
+}
+
+fn consolidated_level(polity: &Polity, settlements: usize, population: isize, world: &World) -> Option<PolityLevel> {
+    let capital_population = polity
+        .capital
+        .as_ref()
+        .map(|c| c.get().population(world))
+        .unwrap_or(0);
+    let city_dominated = capital_population >= CITY_STATE_POPULATION && capital_population * 2 >= population;
+    match polity.level {
+        PolityLevel::Tribe | PolityLevel::Chiefdom if city_dominated => Some(PolityLevel::CityState),
+        PolityLevel::Tribe if settlements >= CHIEFDOM_SETTLEMENTS => Some(PolityLevel::Chiefdom),
+        // only a polity that holds together can make itself a kingdom
+        PolityLevel::Chiefdom | PolityLevel::Confederacy
+            if settlements >= KINGDOM_SETTLEMENTS && individual_event(polity.level.cohesion() as f64) =>
+        {
+            Some(PolityLevel::Kingdom)
+        },
+        PolityLevel::CityState if settlements >= KINGDOM_SETTLEMENTS / 2 => {
+            // either the ruler makes their line permanent, or the notables take over
+            if individual_event(0.5) {
+                Some(PolityLevel::Kingdom)
+            } else {
+                Some(PolityLevel::Republic)
+            }
+        },
+        _ => None,
+    }
+}
+
+fn can_unite(level: PolityLevel) -> bool {
+    level == PolityLevel::Tribe || level == PolityLevel::Chiefdom
+}
+
+pub fn polity_progression(world: &World) {
+    let settlements = settlements_by_polity(world);
+    let borders = polity_borders(world);
+    let population = |polity: &PolityId| -> isize {
+        settlements
+            .get(polity)
+            .map(|ss| ss.iter().map(|s| s.get().population(world)).sum())
+            .unwrap_or(0)
+    };
+    let mut involved: HashSet<PolityId> = HashSet::new();
+    for polity_id in world.iter_storage::<Polity>() {
+        if involved.contains(polity_id) {
+            continue;
+        }
+        let polity = polity_id.get();
+        let own_settlements = settlements.get(polity_id).map(|s| s.len()).unwrap_or(0);
+        let own_population = population(polity_id);
+        let cohesion = polity.level.cohesion() as f64;
+        let fracture_chance = FRACTURE_CHANCE * (1.0 - cohesion) * own_settlements as f64 / CHIEFDOM_SETTLEMENTS as f64;
+        if own_settlements > 1 && individual_event(fracture_chance) {
+            world.add_command(Box::new(FracturePolityCommand(polity_id.clone())));
+            involved.insert(polity_id.clone());
+            continue;
+        }
+        if let Some(level) = consolidated_level(&polity, own_settlements, own_population, world) {
+            world.add_command(Box::new(ChangePolityLevelCommand {
+                polity: polity_id.clone(),
+                level,
+            }));
+            involved.insert(polity_id.clone());
+            continue;
+        }
+        if !can_unite(polity.level) {
+            continue;
+        }
+
+        let neighbors = match borders.get(polity_id) {
+            Some(neighbors) => neighbors,
+            None => continue,
+        };
+        let partners = neighbors
+            .iter()
+            .filter(|n| {
+                !involved.contains(*n)
+                    && n.get().primary_culture == polity.primary_culture
+                    && can_unite(n.get().level)
+                    && population(n) <= own_population
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if partners.is_empty() {
+            continue;
+        }
+
+        let threatened = neighbors
+            .iter()
+            .any(|n| population(n) > own_population * PRESSURE_RATIO);
+        if threatened && individual_event(CONFEDERATION_CHANCE) {
+            for partner in partners.iter() {
+                world.add_command(Box::new(UnitePolitiesCommand {
+                    absorber: polity_id.clone(),
+                    absorbed: partner.clone(),
+                    level: PolityLevel::Confederacy,
+                }));
+                involved.insert(partner.clone());
+            }
+            involved.insert(polity_id.clone());
+        } else if individual_event(UNION_CHANCE * cohesion) {
+            // tribes band into a chiefdom, chiefdoms swallow their smaller kin
+            world.add_command(Box::new(UnitePolitiesCommand {
+                absorber: polity_id.clone(),
+                absorbed: partners[0].clone(),
+                level: PolityLevel::Chiefdom,
+            }));
+            involved.insert(partners[0].clone());
+            involved.insert(polity_id.clone());
+        }
+    }
+}
+
+pub struct ChangePolityLevelCommand {
+    pub polity: PolityId,
+    pub level: PolityLevel,
+}
+
+impl Command for ChangePolityLevelCommand {
+    fn run(&self, world: &mut World) {
+        let from = self.polity.get().level;
+        if from == self.level || !world.contains(&self.polity) {
+            return;
+        }
+        self.polity.get_mut().level = self.level;
+        self.polity.get_mut().successor_law = level_successor_law(self.level);
+        world.events.add(Rc::new(PolityLevelChangedEvent {
+            polity: self.polity.clone(),
+            from,
+            to: self.level,
+        }));
+    }
+}
+
+pub struct UnitePolitiesCommand {
+    pub absorber: PolityId,
+    pub absorbed: PolityId,
+    pub level: PolityLevel,
+}
+
+impl Command for UnitePolitiesCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.absorber) || !world.contains(&self.absorbed) {
+            return;
+        }
+        for settlement in self.absorbed.get().settlements(world) {
+            settlement.get_mut().controller = self.absorber.clone();
+        }
+        world.events.add(Rc::new(PolityUnitedEvent {
+            absorber: self.absorber.clone(),
+            absorbed: self.absorbed.clone(),
+        }));
+        DissolvePolityCommand(self.absorbed.clone()).run(world);
+        ChangePolityLevelCommand {
+            polity: self.absorber.clone(),
+            level: self.level,
+        }.run(world);
+    }
+}
+
+// the settlements furthest from the capital go their own way
+pub struct FracturePolityCommand(pub PolityId);
+
+impl Command for FracturePolityCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.0) {
+            return;
+        }
+        let capital = match self.0.get().capital.clone() {
+            Some(capital) => capital,
+            None => return,
+        };
+        let origin = capital.get().province.get().coordinate;
+        let distance = |s: &SettlementId, from: Coordinate| s.get().province.get().coordinate.dist(from);
+        let mut outlying = self.0.get().settlements(world);
+        outlying.retain(|s| *s != capital);
+        outlying.sort_by_key(|s| (Reverse(distance(s, origin)), s.num()));
+        let seat = match outlying.first() {
+            Some(seat) => seat.clone(),
+            None => return,
+        };
+        // its neighbors follow it out
+        let seat_coordinate = seat.get().province.get().coordinate;
+        let breakaway = outlying
+            .into_iter()
+            .filter(|s| distance(s, seat_coordinate) <= 1)
+            .collect::<Vec<_>>();
+        let culture = seat.get().primary_culture.clone();
+        let name = culture.get().language.get().generate_toponym();
+        let level = if breakaway.len() >= CHIEFDOM_SETTLEMENTS { PolityLevel::Chiefdom } else { PolityLevel::Tribe };
+        let new_polity = add_polity(world, name, culture, level, &mut thread_rng());
+        for settlement in breakaway.iter() {
+            settlement.get_mut().controller = new_polity.clone();
+            for pop in settlement.get().pops.iter() {
+                pop.get_mut().polity = new_polity.clone();
+            }
+        }
+        new_polity.get_mut().capital = Some(seat.clone());
+        let leader = new_polity.get().leader.clone();
+        SetHeadmanCommand {
+            settlement: seat,
+            headman: leader,
+        }
+        .run(world);
+        world.diplomacy.add_opinion(&self.0, &new_polity, -20.0);
+        world.events.add(Rc::new(PolityFracturedEvent {
+            polity: self.0.clone(),
+            breakaway: new_polity,
+            settlements: breakaway,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successor_law_follows_level() {
+        assert_eq!(level_successor_law(PolityLevel::Kingdom), SuccessorLaw::Primogeniture);
+        for level in [PolityLevel::Tribe, PolityLevel::Confederacy, PolityLevel::CityState, PolityLevel::Republic] {
+            assert_eq!(level_successor_law(level), SuccessorLaw::Election);
+        }
+        for _ in 0..20 {
+            let law = level_successor_law(PolityLevel::Chiefdom);
+            assert!(matches!(law, SuccessorLaw::Partible | SuccessorLaw::Seniority), "{:?}", law);
+        }
+    }
+}
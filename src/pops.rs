@@ -5,11 +5,14 @@ use rand_distr::Uniform;
 use crate::*;
 use std::{cell::RefCell, collections::{HashMap, HashSet}, fmt::Debug, hash::Hash, rc::Rc, rc::Weak};
 
+// worth in wheat a worker digs out of a mine each year
+pub const MINE_YIELD: f32 = 30.0;
+
 #[derive(Clone, Debug)]
 pub struct MigrationStatus {
     pub migrating: isize,
     pub dest: ProvinceId,
-    pub date: usize,
+    pub date: Date,
     pub settlement: Option<SettlementId>,
 }
 
@@ -18,6 +21,7 @@ pub struct Pop {
     pub id: usize,
     pub size: isize,
     pub culture: CultureId,
+    pub religion: ReligionId,
     pub settlement: SettlementId,
     pub province: ProvinceId,
     pub kid_buffer: KidBuffer,
@@ -26,6 +30,7 @@ pub struct Pop {
     pub farmed_good: Option<GoodType>,
     pub migration_status: Option<MigrationStatus>,
     pub polity: PolityId,
+    pub unrest: f32,
 }
 
 gen_id!(Pop, PopId);
@@ -108,125 +113,23 @@ pub fn harvest(pop: &PopId, world: &World) {
             pop: pop.clone(),
         }));
     }
-}
-
-#[iron_data]
-pub struct Language {
-    pub id: usize,
-    pub name: String,
-    pub vowels: Vec<String>,
-    pub initial_consonants: Vec<String>,
-    pub middle_consonants: Vec<String>,
-    pub end_consonants: Vec<String>,
-}
-
-fn list_filter_chance(list: &Vec<String>, chance: f32) -> Vec<String> {
-    list.iter()
-        .filter_map(|v| {
-            if rand::random::<f32>() < chance {
-                Some(v.clone())
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<String>>()
-}
-
-fn map_string(list: Vec<&str>) -> Vec<String> {
-    list.iter()
-        .map(|v| String::from(*v))
-        .collect::<Vec<String>>()
-}
-
-pub fn sample_list(list: &Vec<String>) -> String {
-    thread_rng().sample(Slice::new(list).unwrap()).clone()
-}
-
-impl Language {
-    pub fn new() -> Self {
-        let vowel_chance = 0.75;
-        let vowels = list_filter_chance(
-            &map_string(vec![
-                "a", "ae", "e", "i", "ei", "u", "o", "oi", "au", "ou", "ee", "ea", "oa",
-            ]),
-            0.75,
-        );
-        let consonants = list_filter_chance(
-            &map_string(vec![
-                "b", "c", "d", "f", "g", "h", "j", "k", "l", "m", "n", "p", "r", "s", "t", "v",
-                "w", "z", "ss", "th", "st", "ch", "sh",
-            ]),
-            0.75,
-        );
-
-        let initial_consonants = list_filter_chance(&consonants, 0.50);
-        let middle_consonants = list_filter_chance(&consonants, 0.75);
-        let end_consonants = list_filter_chance(&consonants, 0.50);
-
-        Self {
-            id: 0,
-            name: "".to_owned(),
-            vowels,
-            initial_consonants,
-            middle_consonants,
-            end_consonants,
-        }
-    }
-
-    pub fn maybe_vowel(&self, chance: f32) -> Option<String> {
-        if rand::random::<f32>() < chance {
-            Some(sample_list(&self.vowels))
-        } else {
-            None
-        }
-    }
-
-    pub fn generate_name(&self, max_middle: usize) -> String {
-        let mut name: String = String::new();
-        name += &self.maybe_vowel(0.3).unwrap_or("".to_owned());
-        name += &sample_list(&self.initial_consonants);
-        for i in 0..thread_rng().sample(Uniform::new(0, max_middle)) {
-            name += &sample_list(&self.vowels);
-            name += &sample_list(&self.middle_consonants);
-        }
-        name += &sample_list(&self.vowels);
-        name += &sample_list(&self.end_consonants);
-        name += &self.maybe_vowel(0.3).unwrap_or("".to_owned());
-        to_title_case(name.as_str())
-    }
-}
-
-pub enum CultureFeature {
-    Warrior,
-    Seafaring,
-}
-
-#[iron_data]
-pub struct Culture {
-    pub id: usize,
-    pub name: String,
-    pub religion: ReligionId,
-    pub language: LanguageId,
-    pub features: Vec<CultureFeature>,
-}
-
-impl Culture {
-    pub fn generate_character(&self, sex: Sex, age: isize, world: &mut World) -> CharacterId {
-        world.insert(Character {
-            id: 0,
-            name: format!("{} {}", self.language.get().generate_name(2), self.language.get().generate_name(2)),
-            birthday: Date { day: world.date.day - (360 * age + (0..359).choose(&mut thread_rng()).unwrap()) as usize },
-            sex,
-            health: dev_mean_sample(5.0, 60.0) as f32,
-            death: None,
-            features: HashSet::new(),
-            titles: Vec::new(),
+    let mined = pop
+        .get()
+        .settlement
+        .get()
+        .features
+        .iter()
+        .filter_map(|f| match f {
+            SettlementFeature::Mines(good) => Some(*good),
+            _ => None,
         })
+        .collect::<Vec<_>>();
+    for good in mined {
+        // a mine's output is worth about the same whatever it digs up
+        world.add_command(Box::new(AddGoodsCommand {
+            good_type: good,
+            amount: pop.get().size as f32 * MINE_YIELD / good.value(),
+            pop: pop.clone(),
+        }));
     }
 }
-
-#[iron_data]
-pub struct Religion {
-    pub id: usize,
-    pub name: String,
-}
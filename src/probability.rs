@@ -36,11 +36,19 @@ pub fn logistic(x: f64) -> f64 {
 }
 
 pub fn dev_mean_sample(stddev: f64, mean: f64) -> f64 {
-    thread_rng().sample::<f64, StandardNormal>(StandardNormal) * stddev + mean
+    dev_mean_sample_with(stddev, mean, &mut thread_rng())
+}
+
+pub fn dev_mean_sample_with(stddev: f64, mean: f64, rng: &mut impl Rng) -> f64 {
+    rng.sample::<f64, StandardNormal>(StandardNormal) * stddev + mean
 }
 
 pub fn positive_isample(stddev: isize, mean: isize) -> isize {
-    dev_mean_sample(stddev as f64, mean as f64).max(0.0).round() as isize
+    positive_isample_with(stddev, mean, &mut thread_rng())
+}
+
+pub fn positive_isample_with(stddev: isize, mean: isize, rng: &mut impl Rng) -> isize {
+    dev_mean_sample_with(stddev as f64, mean as f64, rng).max(0.0).round() as isize
 }
 
 pub fn sample(stddev: f64) -> f64 {
@@ -0,0 +1,434 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{prelude::SliceRandom, random, thread_rng, Rng};
+use strum::EnumIter;
+
+use crate::*;
+
+pub const TEMPLE_COST: f32 = 10000.0;
+pub const TEMPLE_STABILITY: f32 = 0.1;
+pub const HOLY_SITES: usize = 3;
+pub const HOLY_SITE_STABILITY: f32 = 0.05;
+// yearly chance for a pop under full pressure to take up another faith
+pub const CONVERSION_CHANCE: f32 = 0.05;
+pub const SYNCRETISM_CHANCE: f32 = 0.02;
+// the lesser faith needs this share of the settlement before the two blend
+pub const SYNCRETISM_MIN_SHARE: f32 = 0.2;
+pub const STATE_RELIGION_CHANCE: f32 = 0.1;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
+pub enum Doctrine {
+    Martial,
+    Pacifist,
+    Proselytizing,
+    Tolerant,
+    Ascetic,
+    Ancestral,
+}
+
+use Doctrine::*;
+
+pub const DOCTRINES: [Doctrine; 6] = [Martial, Pacifist, Proselytizing, Tolerant, Ascetic, Ancestral];
+
+impl Doctrine {
+    pub fn effects(self) -> &'static [(TraitEffect, f32)] {
+        match self {
+            Martial => &[(TraitEffect::Aggression, 0.1)],
+            Pacifist => &[(TraitEffect::Aggression, -0.1), (TraitEffect::Diplomacy, 0.05)],
+            Proselytizing => &[],
+            Tolerant => &[(TraitEffect::Diplomacy, 0.05)],
+            Ascetic => &[(TraitEffect::Stability, 0.05), (TraitEffect::Stewardship, -0.05)],
+            Ancestral => &[(TraitEffect::Stability, 0.05)],
+        }
+    }
+
+    pub fn opposite(self) -> Option<Doctrine> {
+        match self {
+            Martial => Some(Pacifist),
+            Pacifist => Some(Martial),
+            Proselytizing => Some(Tolerant),
+            Tolerant => Some(Proselytizing),
+            _ => None,
+        }
+    }
+
+    pub fn random_doctrines(count: usize, rng: &mut impl Rng) -> Vec<Doctrine> {
+        let mut doctrines = Vec::new();
+        while doctrines.len() < count {
+            let doctrine = *DOCTRINES.choose(rng).unwrap();
+            if !doctrines.contains(&doctrine) && !doctrine.opposite().is_some_and(|o| doctrines.contains(&o)) {
+                doctrines.push(doctrine);
+            }
+        }
+        doctrines
+    }
+}
+
+#[iron_data]
+pub struct Religion {
+    pub id: usize,
+    pub name: String,
+    pub deities: Vec<String>,
+    pub doctrines: Vec<Doctrine>,
+    pub holy_sites: Vec<ProvinceId>,
+    // the clergy is led by a single high priest
+    pub head: Option<CharacterId>,
+    // the faiths a syncretic religion grew out of
+    pub origins: Vec<ReligionId>,
+    pub founded: Date,
+}
+
+impl Religion {
+    pub fn has_doctrine(&self, doctrine: Doctrine) -> bool {
+        self.doctrines.contains(&doctrine)
+    }
+
+    pub fn doctrine_modifier(&self, effect: TraitEffect) -> f32 {
+        self.doctrines
+            .iter()
+            .flat_map(|d| d.effects().iter())
+            .filter(|(e, _)| *e == effect)
+            .map(|(_, v)| v)
+            .sum()
+    }
+
+    pub fn is_holy_site(&self, province: &ProvinceId) -> bool {
+        self.holy_sites.contains(province)
+    }
+}
+
+pub fn add_religion(
+    world: &mut World,
+    language: &LanguageId,
+    doctrines: Vec<Doctrine>,
+    origins: Vec<ReligionId>,
+    rng: &mut impl Rng,
+) -> ReligionId {
+    let (name, deities) = {
+        let language = language.get();
+        let deities = (0..rng.gen_range(1..=4))
+            .map(|_| {
+                let sex = if rng.gen::<bool>() { Sex::Male } else { Sex::Female };
+                language.generate_given_name(sex)
+            })
+            .collect::<Vec<_>>();
+        (language.generate_name(2), deities)
+    };
+    world.insert(Religion {
+        id: 0,
+        name,
+        deities,
+        doctrines,
+        holy_sites: Vec::new(),
+        head: None,
+        origins,
+        founded: world.date,
+    })
+}
+
+// sacred hills and rivers near the faithful
+pub fn assign_holy_sites(world: &World, rng: &mut impl Rng) {
+    let mut religions = world.iter_storage::<Religion>().cloned().collect::<Vec<_>>();
+    religions.sort_by_key(|r| r.num());
+    let mut settlements = world.iter_storage::<Settlement>().cloned().collect::<Vec<_>>();
+    settlements.sort_by_key(|s| s.num());
+    for religion in religions.iter() {
+        let mut sites = settlements
+            .iter()
+            .filter(|s| s.get().pops.iter().any(|p| p.get().religion == *religion))
+            .map(|s| {
+                let s = s.get();
+                let sacred = s.features.contains(&SettlementFeature::Hilltop) || s.features.contains(&SettlementFeature::Riverside);
+                (s.province.clone(), sacred as usize as f32 + rng.gen::<f32>())
+            })
+            .collect::<Vec<_>>();
+        sites.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        let mut religion = religion.get_mut();
+        for (province, _) in sites {
+            if religion.holy_sites.len() >= HOLY_SITES {
+                break;
+            }
+            if !religion.holy_sites.contains(&province) {
+                religion.holy_sites.push(province);
+            }
+        }
+    }
+}
+
+pub fn religion_factor(polity: &PolityId, effect: TraitEffect, world: &World) -> f32 {
+    let religion = polity.get().religion.clone();
+    let mut modifier = religion.get().doctrine_modifier(effect);
+    if effect == TraitEffect::Stability {
+        let held = religion
+            .get()
+            .holy_sites
+            .iter()
+            .filter(|p| p.get().controller.as_ref() == Some(polity))
+            .count();
+        modifier += held as f32 * HOLY_SITE_STABILITY;
+    }
+    modifier
+}
+
+// the faith followed by most people in and around a province
+fn local_majority(province: &ProvinceId, world: &World) -> Option<(ReligionId, f32)> {
+    let coordinate = province.get().coordinate;
+    let mut followers: HashMap<ReligionId, isize> = HashMap::new();
+    let provinces = std::iter::once(coordinate)
+        .chain(coordinate.neighbors_iter())
+        .filter_map(|c| world.get_province_coordinate(c));
+    for province in provinces {
+        for settlement in province.get().settlements.iter() {
+            for pop in settlement.get().pops.iter() {
+                let pop = pop.get();
+                *followers.entry(pop.religion.clone()).or_insert(0) += pop.size;
+            }
+        }
+    }
+    let total = followers.values().sum::<isize>();
+    followers
+        .into_iter()
+        .max_by_key(|(_, size)| *size)
+        .map(|(religion, size)| (religion, size as f32 / total.max(1) as f32))
+}
+
+pub fn religion_tick(world: &World) {
+    // faiths nobody keeps any more fade away, unless a ruler or a people still claims them
+    let mut kept: HashSet<ReligionId> = world.iter_storage::<Pop>().map(|p| p.get().religion.clone()).collect();
+    kept.extend(world.iter_storage::<Polity>().map(|p| p.get().religion.clone()));
+    kept.extend(world.iter_storage::<Culture>().map(|c| c.get().religion.clone()));
+    for religion in world.iter_storage::<Religion>() {
+        if !kept.contains(religion) {
+            world.add_command(Box::new(ForgetReligionCommand(religion.clone())));
+        } else if !religion.get().head.as_ref().is_some_and(|h| h.get().alive()) {
+            world.add_command(Box::new(AppointReligiousHeadCommand(religion.clone())));
+        }
+    }
+    for pop_id in world.iter_storage::<Pop>() {
+        let pop = pop_id.get();
+        if !world.contains(&pop.polity) {
+            continue;
+        }
+        let state_religion = pop.polity.get().religion.clone();
+        let mut pressures = Vec::new();
+        if pop.religion != state_religion {
+            let mut pressure = 1.0;
+            if state_religion.get().has_doctrine(Proselytizing) {
+                pressure *= 1.5;
+            }
+            if state_religion.get().has_doctrine(Tolerant) {
+                pressure *= 0.5;
+            }
+            if pop.settlement.get().buildings.contains(&Building::Temple) {
+                pressure *= 2.0;
+            }
+            pressures.push((state_religion, pressure));
+        }
+        if let Some((local, share)) = local_majority(&pop.province, world) {
+            if local != pop.religion {
+                pressures.push((local, share));
+            }
+        }
+        // the faithful hold on harder near their holy places
+        let resistance = if pop.religion.get().is_holy_site(&pop.province) { 0.5 } else { 1.0 };
+        for (religion, pressure) in pressures {
+            if individual_event((CONVERSION_CHANCE * pressure * resistance) as f64) {
+                world.add_command(Box::new(ConvertPopCommand {
+                    pop: pop_id.clone(),
+                    religion,
+                }));
+                break;
+            }
+        }
+    }
+    for settlement in world.iter_storage::<Settlement>() {
+        let s = settlement.get();
+        let mixed = s.pops.iter().any(|a| s.pops.iter().any(|b| a.get().culture != b.get().culture && a.get().religion != b.get().religion));
+        if mixed && individual_event(SYNCRETISM_CHANCE as f64) {
+            world.add_command(Box::new(SyncretismCommand(settlement.clone())));
+        }
+    }
+    // rulers come around to the faith of their subjects
+    let mut followers: HashMap<PolityId, HashMap<ReligionId, isize>> = HashMap::new();
+    for pop in world.iter_storage::<Pop>() {
+        let pop = pop.get();
+        *followers.entry(pop.polity.clone()).or_default().entry(pop.religion.clone()).or_insert(0) += pop.size;
+    }
+    for (polity, religions) in followers.into_iter() {
+        if !world.contains(&polity) {
+            continue;
+        }
+        let total = religions.values().sum::<isize>();
+        if let Some((religion, size)) = religions.into_iter().max_by_key(|(_, size)| *size) {
+            if religion != polity.get().religion && size * 2 > total && individual_event(STATE_RELIGION_CHANCE as f64) {
+                world.add_command(Box::new(ChangeStateReligionCommand { polity, religion }));
+            }
+        }
+    }
+}
+
+pub struct ConvertPopCommand {
+    pub pop: PopId,
+    pub religion: ReligionId,
+}
+
+impl Command for ConvertPopCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.pop) {
+            return;
+        }
+        let (settlement, culture, size) = {
+            let pop = self.pop.get();
+            (pop.settlement.clone(), pop.culture.clone(), pop.size)
+        };
+        // join coreligionists of the same culture if there are any
+        let existing = settlement
+            .get()
+            .pops
+            .iter()
+            .find(|p| **p != self.pop && p.get().culture == culture && p.get().religion == self.religion)
+            .cloned();
+        match existing {
+            Some(existing) => {
+                existing.get_mut().size += size;
+                DestroyPopCommand(self.pop.clone()).run(world);
+            },
+            None => self.pop.get_mut().religion = self.religion.clone(),
+        }
+    }
+}
+
+pub struct SyncretismCommand(pub SettlementId);
+
+impl Command for SyncretismCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.0) {
+            return;
+        }
+        let mut followers: HashMap<ReligionId, isize> = HashMap::new();
+        for pop in self.0.get().pops.iter() {
+            let pop = pop.get();
+            *followers.entry(pop.religion.clone()).or_insert(0) += pop.size;
+        }
+        let total = followers.values().sum::<isize>();
+        let mut religions = followers.into_iter().collect::<Vec<_>>();
+        religions.sort_by_key(|(_, size)| -size);
+        if religions.len() < 2 || (religions[1].1 as f32) < total as f32 * SYNCRETISM_MIN_SHARE {
+            return;
+        }
+        let (a, b) = (religions[0].0.clone(), religions[1].0.clone());
+        let pops = self
+            .0
+            .get()
+            .pops
+            .iter()
+            .filter(|p| p.get().religion == a || p.get().religion == b)
+            .cloned()
+            .collect::<Vec<_>>();
+        // the same two faiths have blended before somewhere else
+        let existing = world
+            .iter_storage::<Religion>()
+            .find(|r| {
+                let origins = &r.get().origins;
+                origins.len() == 2 && origins.contains(&a) && origins.contains(&b)
+            })
+            .cloned();
+        if let Some(religion) = existing {
+            for pop in pops {
+                ConvertPopCommand {
+                    pop,
+                    religion: religion.clone(),
+                }
+                .run(world);
+            }
+            return;
+        }
+        let mut doctrines = Vec::new();
+        for religion in [&a, &b] {
+            for doctrine in religion.get().doctrines.iter() {
+                if !doctrines.contains(doctrine) && !doctrine.opposite().is_some_and(|o| doctrines.contains(&o)) {
+                    doctrines.push(*doctrine);
+                }
+            }
+        }
+        doctrines.truncate(3);
+        let language = self.0.get().primary_culture.get().language.clone();
+        let religion = add_religion(world, &language, doctrines, vec![a.clone(), b.clone()], &mut thread_rng());
+        // the new faith keeps a god or two from each of its parents
+        let mut deities = a.get().deities.iter().take(2).cloned().collect::<Vec<_>>();
+        deities.extend(b.get().deities.iter().take(2).cloned());
+        {
+            let mut religion = religion.get_mut();
+            religion.deities = deities;
+            religion.holy_sites.push(self.0.get().province.clone());
+        }
+        for pop in pops {
+            ConvertPopCommand {
+                pop,
+                religion: religion.clone(),
+            }
+            .run(world);
+        }
+        world.events.add(Rc::new(SyncretismEvent {
+            religion,
+            origins: (a, b),
+            settlement: self.0.clone(),
+        }));
+    }
+}
+
+pub struct ChangeStateReligionCommand {
+    pub polity: PolityId,
+    pub religion: ReligionId,
+}
+
+impl Command for ChangeStateReligionCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.polity) || !world.contains(&self.religion) {
+            return;
+        }
+        let from = std::mem::replace(&mut self.polity.get_mut().religion, self.religion.clone());
+        world.events.add(Rc::new(StateReligionChangedEvent {
+            polity: self.polity.clone(),
+            from,
+            to: self.religion.clone(),
+        }));
+    }
+}
+
+pub struct ForgetReligionCommand(pub ReligionId);
+
+impl Command for ForgetReligionCommand {
+    fn run(&self, world: &mut World) {
+        if let Some(head) = self.0.get_mut().head.take() {
+            head.get_mut().titles.retain(|t| !matches!(t, Title::ReligiousHead(r) if *r == self.0));
+        }
+        world.remove(&self.0);
+    }
+}
+
+pub struct AppointReligiousHeadCommand(pub ReligionId);
+
+impl Command for AppointReligiousHeadCommand {
+    fn run(&self, world: &mut World) {
+        // the clergy comes from the people who keep the faith
+        let culture = world
+            .iter_storage::<Pop>()
+            .filter(|p| p.get().religion == self.0)
+            .max_by_key(|p| p.get().size)
+            .map(|p| p.get().culture.clone());
+        let culture = match culture {
+            Some(culture) => culture,
+            None => return,
+        };
+        let age = positive_isample(8, 50);
+        let sex = culture.get().gender_law.ruler_sex();
+        let head = culture.get().generate_character(sex, age, world, &mut thread_rng());
+        head.get_mut().titles.push(Title::ReligiousHead(self.0.clone()));
+        self.0.get_mut().head = Some(head.clone());
+        world.events.add(Rc::new(ReligiousHeadEvent {
+            religion: self.0.clone(),
+            head,
+        }));
+    }
+}
@@ -10,12 +10,14 @@ use ggez::{
 };
 
 use crate::{
-    IronData, IronId, Point2, Province, ProvinceId, World, SQRT_3, TILE_SIZE_X, TILE_SIZE_Y,
+    IronData, IronId, PolityId, Point2, Province, ProvinceId, World, SQRT_3, TILE_SIZE_X,
+    TILE_SIZE_Y,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum OverlayKind {
     Population,
+    Political,
 }
 
 pub trait Overlay {
@@ -82,6 +84,64 @@ impl Overlay for PopOverlay {
     }
 }
 
+// spread polity hues around the color wheel so neighbors rarely match
+fn polity_color(polity: &PolityId) -> Color {
+    let hue = (polity.num() as f32 * 0.618034).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    Color::new(r, g, b, 0.6)
+}
+
+struct PoliticalOverlay {
+    map: MeshBatch,
+}
+
+impl Overlay for PoliticalOverlay {
+    fn new(ctx: &mut Context) -> Self
+    where
+        Self: Sized,
+    {
+        let hex = hex_mesh(ctx, Color::new(1.0, 1.0, 1.0, 1.0));
+        Self {
+            map: MeshBatch::new(hex).unwrap(),
+        }
+    }
+
+    fn update(&mut self, world: &World) {
+        self.map.clear();
+        let (w, h) = tile_sizes();
+        for province in world.iter_storage::<Province>() {
+            if let Some(controller) = &province.get().controller {
+                let province_pixel_pos = province.get().coordinate.base_pixel_pos();
+                let hex_dest = [
+                    province_pixel_pos.x - w / 2.0,
+                    province_pixel_pos.y - h / 2.0,
+                ];
+                self.map.add(
+                    DrawParam::new()
+                        .dest(hex_dest)
+                        .color(polity_color(controller)),
+                );
+            }
+        }
+    }
+
+    fn map(&mut self) -> &mut MeshBatch {
+        &mut self.map
+    }
+
+    fn kind(&self) -> OverlayKind {
+        OverlayKind::Political
+    }
+}
+
 pub struct RenderContext {
     province_meshes: HashSet<ProvinceId>,
     mesh_map: MeshBatch,
@@ -150,9 +210,10 @@ impl RenderContext {
             }
         }
 
-        self.overlay = Some(Box::new(match kind {
-            OverlayKind::Population => PopOverlay::new(ctx),
-        }));
+        self.overlay = Some(match kind {
+            OverlayKind::Population => Box::new(PopOverlay::new(ctx)),
+            OverlayKind::Political => Box::new(PoliticalOverlay::new(ctx)),
+        });
     }
 
     pub fn generate_province_meshes(&mut self, world: &World, ctx: &mut Context) {
@@ -172,7 +233,7 @@ impl RenderContext {
         self.mesh_map.add(
             DrawParam::new()
                 .dest(hex_dest)
-                .color(province.get().terrain.color()),
+                .color(province.get().color()),
         );
         self.outline_map.add(DrawParam::new().dest(hex_dest));
         self.province_meshes.insert(province.clone());
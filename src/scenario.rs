@@ -0,0 +1,469 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::*;
+
+// a hand written starting world, see scenarios/aegean.ini for the format
+pub struct Scenario {
+    pub name: String,
+    sections: Vec<IniSection>,
+}
+
+// names the scenario's areas and seas
+pub const GEOGRAPHY_SEED: u64 = 1;
+// fills in whatever the scenario leaves unsaid when `[scenario]` gives no seed
+pub const SCENARIO_SEED: u64 = 0;
+
+// letters used to draw the `[map]` rows
+fn map_terrain(c: char) -> Option<Terrain> {
+    match c {
+        '~' => Some(Terrain::Ocean),
+        '.' => Some(Terrain::Plains),
+        'h' => Some(Terrain::Hills),
+        'M' => Some(Terrain::Mountains),
+        'd' => Some(Terrain::Desert),
+        'm' => Some(Terrain::Marsh),
+        'f' => Some(Terrain::Forest),
+        _ => None,
+    }
+}
+
+// `[kind name]` headers
+fn split_header(section: &IniSection) -> (&str, &str) {
+    match section.name.split_once(' ') {
+        Some((kind, name)) => (kind, name.trim()),
+        None => (section.name.as_str(), ""),
+    }
+}
+
+fn lookup<T: Clone>(names: &HashMap<String, T>, kind: &str, name: &str) -> Result<T, String> {
+    names.get(name).cloned().ok_or_else(|| format!("no {} named '{}'", kind, name))
+}
+
+fn required<'a>(section: &'a IniSection, key: &str) -> Result<&'a str, String> {
+    section.get(key).ok_or_else(|| format!("missing '{}'", key))
+}
+
+impl Scenario {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let sections = parse_ini(text)?;
+        let name = sections
+            .iter()
+            .find(|s| s.name == "scenario")
+            .and_then(|s| s.get("name"))
+            .unwrap_or("Untitled")
+            .to_owned();
+        for section in sections.iter() {
+            let (kind, name) = split_header(section);
+            let known = ["scenario", "map", "province", "language", "religion", "culture", "polity", "settlement"];
+            if !known.contains(&kind) {
+                return Err(format!("unknown section [{}]", section.name));
+            }
+            if name.is_empty() && !["scenario", "map"].contains(&kind) {
+                return Err(format!("section [{}] needs a name", kind));
+            }
+        }
+        Ok(Self { name, sections })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn sections<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = (&'a str, &'a IniSection)> + 'a {
+        self.sections.iter().filter_map(move |s| {
+            let (k, name) = split_header(s);
+            if k == kind {
+                Some((name, s))
+            } else {
+                None
+            }
+        })
+    }
+
+    // sections are applied kind by kind, so they can be written in any order
+    pub fn apply(&self, world: &mut World) -> Result<(), String> {
+        let header = self.sections("scenario").next().map(|(_, s)| s);
+        let climate: Climate = match header.and_then(|s| s.get("climate")) {
+            Some(value) => parse_variant("climate", value)?,
+            None => Climate::Mild,
+        };
+        if let Some(year) = header.and_then(|s| s.get("year")) {
+            world.date = Date::from_year(Date::parse_year(year)?);
+        }
+        let seed = match header.and_then(|s| s.get("seed")) {
+            Some(seed) => parse_value("seed", seed)?,
+            None => SCENARIO_SEED,
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        self.apply_map(world, header, climate)?;
+        for (name, section) in self.sections("province") {
+            self.apply_province(world, name, section).map_err(|e| format!("[province {}]: {}", name, e))?;
+        }
+        mark_coasts(world);
+        build_geography(world, GEOGRAPHY_SEED);
+
+        let mut languages: HashMap<String, LanguageId> = HashMap::new();
+        for (name, section) in self.sections("language") {
+            let seed = match section.get("seed") {
+                Some(seed) => parse_value("seed", seed).map_err(|e| format!("[language {}]: {}", name, e))?,
+                None => rng.gen(),
+            };
+            let mut language = Language::new(seed, world.date);
+            language.name = name.to_owned();
+            languages.insert(name.to_owned(), world.insert(language));
+        }
+
+        let mut religions: HashMap<String, ReligionId> = HashMap::new();
+        for (name, section) in self.sections("religion") {
+            let religion = self
+                .apply_religion(world, name, section, &languages, &mut rng)
+                .map_err(|e| format!("[religion {}]: {}", name, e))?;
+            religions.insert(name.to_owned(), religion);
+        }
+
+        let mut cultures: HashMap<String, CultureId> = HashMap::new();
+        for (name, section) in self.sections("culture") {
+            let culture = self
+                .apply_culture(world, name, section, &languages, &religions, &mut rng)
+                .map_err(|e| format!("[culture {}]: {}", name, e))?;
+            cultures.insert(name.to_owned(), culture);
+        }
+
+        let mut polities: HashMap<String, PolityId> = HashMap::new();
+        for (name, section) in self.sections("polity") {
+            let polity = self
+                .apply_polity(world, name, section, &cultures, &religions, &mut rng)
+                .map_err(|e| format!("[polity {}]: {}", name, e))?;
+            polities.insert(name.to_owned(), polity);
+        }
+
+        for (name, section) in self.sections("settlement") {
+            self.apply_settlement(world, name, section, &cultures, &polities, &mut rng)
+                .map_err(|e| format!("[settlement {}]: {}", name, e))?;
+        }
+        for (name, polity) in polities.iter() {
+            if polity.get().capital.is_none() {
+                return Err(format!("[polity {}]: has no settlements", name));
+            }
+        }
+
+        UpdateTerritoryCommand.run(world);
+        // religions without listed holy sites get them from where their faithful live
+        assign_holy_sites(world, &mut rng);
+        Ok(())
+    }
+
+    fn apply_map(&self, world: &mut World, header: Option<&IniSection>, climate: Climate) -> Result<(), String> {
+        let rows = self
+            .sections("map")
+            .flat_map(|(_, s)| s.entries.iter().filter(|(k, _)| k == "row").map(|(_, v)| v.as_str()))
+            .collect::<Vec<_>>();
+        let fill = match header.and_then(|s| s.get("fill")) {
+            Some(value) => parse_variant("fill", value)?,
+            None => Terrain::Ocean,
+        };
+        let width = match header.and_then(|s| s.get("width")) {
+            Some(value) => parse_value("width", value)?,
+            None => rows.iter().map(|r| r.chars().count()).max().unwrap_or(0) as isize,
+        };
+        let height = match header.and_then(|s| s.get("height")) {
+            Some(value) => parse_value("height", value)?,
+            None => rows.len() as isize,
+        };
+        if width < 1 || height < 1 {
+            return Err("the scenario has no map, give it a [map] or a width and height".to_owned());
+        }
+        world.map_size = (width, height);
+        for j in 0..height {
+            let row = rows.get(j as usize).map(|r| r.chars().collect::<Vec<_>>()).unwrap_or_default();
+            for i in 0..width {
+                let terrain = match row.get(i as usize) {
+                    Some(&c) => map_terrain(c).ok_or_else(|| format!("[map]: unknown terrain '{}' in row {}", c, j))?,
+                    None => fill,
+                };
+                world.insert_province(Province {
+                    id: 0,
+                    terrain,
+                    climate,
+                    coordinate: Coordinate::from_offset(i, j),
+                    harvest_month: harvest_month(climate),
+                    settlements: Vec::new(),
+                    features: HashSet::new(),
+                    controller: None,
+                    coastal: false,
+                    river: None,
+                    deposits: Vec::new(),
+                    landmass: None,
+                    area: None,
+                    sea_zones: Vec::new(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_province(&self, world: &mut World, name: &str, section: &IniSection) -> Result<(), String> {
+        let coordinate = parse_coordinate("province", name)?;
+        let province_id = world
+            .get_province_coordinate(coordinate)
+            .ok_or_else(|| format!("{} is off the map", coordinate))?;
+        let mut province = province_id.get_mut();
+        if let Some(terrain) = section.get("terrain") {
+            province.terrain = parse_variant("terrain", terrain)?;
+        }
+        if let Some(climate) = section.get("climate") {
+            province.climate = parse_variant("climate", climate)?;
+            province.harvest_month = harvest_month(province.climate);
+        }
+        if let Some(features) = section.get("features") {
+            for feature in parse_list(features) {
+                province.features.insert(parse_variant("features", feature)?);
+            }
+        }
+        if let Some(deposits) = section.get("deposits") {
+            for good in parse_list(deposits) {
+                province.deposits.push(parse_variant("deposits", good)?);
+            }
+        }
+        if let Some(river) = section.get("river") {
+            province.river = Some(parse_coordinate("river", river)?);
+        }
+        Ok(())
+    }
+
+    fn apply_religion(
+        &self,
+        world: &mut World,
+        name: &str,
+        section: &IniSection,
+        languages: &HashMap<String, LanguageId>,
+        rng: &mut StdRng,
+    ) -> Result<ReligionId, String> {
+        let language = match section.get("language") {
+            Some(language) => lookup(languages, "language", language)?,
+            None => languages.values().next().cloned().ok_or("no language to name its gods in")?,
+        };
+        let mut doctrines = Vec::new();
+        for doctrine in parse_list(section.get("doctrines").unwrap_or("")) {
+            doctrines.push(parse_variant("doctrines", doctrine)?);
+        }
+        let religion_id = add_religion(world, &language, doctrines, Vec::new(), rng);
+        let mut religion = religion_id.get_mut();
+        religion.name = name.to_owned();
+        if let Some(deities) = section.get("deities") {
+            religion.deities = parse_list(deities).into_iter().map(|d| d.to_owned()).collect();
+        }
+        for site in parse_list(section.get("holy_sites").unwrap_or("")) {
+            let coordinate = parse_coordinate("holy_sites", site)?;
+            let province = world
+                .get_province_coordinate(coordinate)
+                .ok_or_else(|| format!("holy site {} is off the map", coordinate))?;
+            religion.holy_sites.push(province);
+        }
+        drop(religion);
+        Ok(religion_id)
+    }
+
+    fn apply_culture(
+        &self,
+        world: &mut World,
+        name: &str,
+        section: &IniSection,
+        languages: &HashMap<String, LanguageId>,
+        religions: &HashMap<String, ReligionId>,
+        rng: &mut StdRng,
+    ) -> Result<CultureId, String> {
+        let language = lookup(languages, "language", required(section, "language")?)?;
+        let religion = lookup(religions, "religion", required(section, "religion")?)?;
+        let mut features = Vec::new();
+        for feature in parse_list(section.get("features").unwrap_or("")) {
+            features.push(parse_variant("features", feature)?);
+        }
+        let gender_law = match section.get("gender_law") {
+            Some(law) => parse_variant("gender_law", law)?,
+            None => GenderLaw::random(rng),
+        };
+        Ok(world.insert(Culture {
+            id: 0,
+            name: name.to_owned(),
+            language,
+            religion,
+            features,
+            gender_law,
+            parent: None,
+            founded: world.date,
+            martial_history: 0.0,
+        }))
+    }
+
+    fn apply_polity(
+        &self,
+        world: &mut World,
+        name: &str,
+        section: &IniSection,
+        cultures: &HashMap<String, CultureId>,
+        religions: &HashMap<String, ReligionId>,
+        rng: &mut StdRng,
+    ) -> Result<PolityId, String> {
+        let culture_id = lookup(cultures, "culture", required(section, "culture")?)?;
+        let level = match section.get("level") {
+            Some(level) => parse_variant("level", level)?,
+            None => PolityLevel::Tribe,
+        };
+        let polity_id = match section.get("leader") {
+            Some(leader_name) => {
+                let age = match section.get("leader_age") {
+                    Some(age) => parse_value("leader_age", age)?,
+                    None => positive_isample_with(8, 45, rng),
+                };
+                let sex = match section.get("leader_sex") {
+                    Some(sex) => parse_variant("leader_sex", sex)?,
+                    None => culture_id.get().gender_law.ruler_sex_with(rng),
+                };
+                let leader = culture_id.get().generate_character(sex, age, world, rng);
+                leader.get_mut().name = leader_name.to_owned();
+                add_polity_with_leader(world, name.to_owned(), culture_id, level, leader)
+            }
+            None => add_polity(world, name.to_owned(), culture_id, level, rng),
+        };
+        if let Some(religion) = section.get("religion") {
+            polity_id.get_mut().religion = lookup(religions, "religion", religion)?;
+        }
+        Ok(polity_id)
+    }
+
+    fn apply_settlement(
+        &self,
+        world: &mut World,
+        name: &str,
+        section: &IniSection,
+        cultures: &HashMap<String, CultureId>,
+        polities: &HashMap<String, PolityId>,
+        rng: &mut StdRng,
+    ) -> Result<SettlementId, String> {
+        let polity_id = lookup(polities, "polity", required(section, "polity")?)?;
+        let coordinate = parse_coordinate("province", required(section, "province")?)?;
+        let province_id = world
+            .get_province_coordinate(coordinate)
+            .ok_or_else(|| format!("{} is off the map", coordinate))?;
+        if province_id.get().terrain == Terrain::Ocean {
+            return Err(format!("{} is at sea", coordinate));
+        }
+        // `pops = Achaean 400, Minoan 50`, the first group sets the settlement's culture
+        let mut pops = Vec::new();
+        for pop in parse_list(required(section, "pops")?) {
+            let (culture, size) = pop.rsplit_once(' ').ok_or_else(|| format!("bad pop '{}', expected 'Culture size'", pop))?;
+            let size: isize = parse_value("pops", size)?;
+            if size < 1 {
+                return Err(format!("pop '{}' needs a size of at least 1", pop));
+            }
+            pops.push((lookup(cultures, "culture", culture.trim())?, size));
+        }
+        let (culture_id, size) = pops.first().cloned().ok_or("a settlement needs pops")?;
+        let settlement_id = add_settlement(world, culture_id, province_id, polity_id, size, rng);
+        settlement_id.get_mut().name = name.to_owned();
+        for (culture_id, size) in pops.into_iter().skip(1) {
+            let religion = culture_id.get().religion.clone();
+            settlement_id.get_mut().add_pops(world, culture_id, religion, size);
+        }
+        for building in parse_list(section.get("buildings").unwrap_or("")) {
+            let building: Building = parse_variant("buildings", building)?;
+            settlement_id.get_mut().buildings.insert(building);
+        }
+        Ok(settlement_id)
+    }
+}
+
+pub fn load_scenario(world: &mut World, path: &str) -> Result<String, String> {
+    let scenario = Scenario::load(path)?;
+    scenario.apply(world).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(scenario.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TINY: &str = "
+[scenario]
+name = Tiny
+year = 500 BC
+seed = 7
+
+[map]
+row = ~...~
+row = ~.h.~
+
+[language Old]
+seed = 3
+
+[religion Faith]
+language = Old
+
+[culture Folk]
+language = Old
+religion = Faith
+gender_law = agnatic
+
+[polity Realm]
+culture = Folk
+level = chiefdom
+
+[settlement Hearth]
+polity = Realm
+province = 1 0
+pops = Folk 120
+";
+
+    #[test]
+    fn parse_checks_section_kinds_and_names() {
+        assert_eq!(Scenario::parse(TINY).unwrap().name, "Tiny");
+        assert_eq!(Scenario::parse("[map]\nrow = ..\n").unwrap().name, "Untitled");
+        assert_eq!(Scenario::parse("[dragon Smaug]\n").err(), Some("unknown section [dragon Smaug]".to_owned()));
+        assert_eq!(Scenario::parse("[polity]\n").err(), Some("section [polity] needs a name".to_owned()));
+        assert!(Scenario::parse("[scenario\n").is_err());
+    }
+
+    #[test]
+    fn apply_builds_the_world() {
+        let mut world = World::default();
+        Scenario::parse(TINY).unwrap().apply(&mut world).unwrap();
+        assert_eq!(world.map_size, (5, 2));
+        assert_eq!(world.date.year(), -500);
+        let settlement = world.iter_storage::<Settlement>().next().unwrap().clone();
+        assert_eq!(settlement.get().name, "Hearth");
+        assert_eq!(settlement.get().population(&world), 120);
+        assert_eq!(settlement.get().controller.get().level, PolityLevel::Chiefdom);
+        assert_eq!(settlement.get().controller.get().capital, Some(settlement.clone()));
+    }
+
+    #[test]
+    fn apply_is_the_same_for_the_same_seed() {
+        let leader = || {
+            let mut world = World::default();
+            Scenario::parse(TINY).unwrap().apply(&mut world).unwrap();
+            let polity = world.iter_storage::<Polity>().next().unwrap().clone();
+            let leader = polity.get().leader.clone();
+            let leader = leader.get();
+            (leader.name.clone(), leader.birthday)
+        };
+        assert_eq!(leader(), leader());
+    }
+
+    #[test]
+    fn apply_rejects_bad_settlements() {
+        for (from, to, error) in [
+            ("pops = Folk 120", "pops = Folk 0", "[settlement Hearth]: pop 'Folk 0' needs a size of at least 1"),
+            ("province = 1 0", "province = 0 0", "[settlement Hearth]: 0, 0 is at sea"),
+            ("pops = Folk 120", "pops = Elves 120", "[settlement Hearth]: no culture named 'Elves'"),
+        ] {
+            let mut world = World::default();
+            let result = Scenario::parse(&TINY.replace(from, to)).unwrap().apply(&mut world);
+            assert_eq!(result.err(), Some(error.to_owned()));
+        }
+    }
+}
@@ -25,6 +25,9 @@ pub enum StorageType {
     Language,
     Polity,
     Character,
+    Army,
+    Dynasty,
+    Region,
 }
 
 impl StorageType {
@@ -45,6 +48,12 @@ impl StorageType {
             Self::Polity
         } else if TypeId::of::<T>() == TypeId::of::<Character>() {
             Self::Character
+        } else if TypeId::of::<T>() == TypeId::of::<Army>() {
+            Self::Army
+        } else if TypeId::of::<T>() == TypeId::of::<Dynasty>() {
+            Self::Dynasty
+        } else if TypeId::of::<T>() == TypeId::of::<Region>() {
+            Self::Region
         } else {
             panic!("could not match Id type to storage, {}", stringify! {T});
         }
@@ -223,6 +232,9 @@ impl Default for Storages {
         init_storage!(Language);
         init_storage!(Polity);
         init_storage!(Character);
+        init_storage!(Army);
+        init_storage!(Dynasty);
+        init_storage!(Region);
         Self { storages }
     }
 }
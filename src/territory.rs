@@ -0,0 +1,125 @@
+use std::{cmp::Reverse, collections::{HashMap, HashSet}};
+
+use crate::*;
+
+// how far a settlement projects influence over unsettled land
+pub const INFLUENCE_RADIUS: isize = 2;
+// below this nobody bothers to claim a province
+pub const MIN_CLAIM_INFLUENCE: f32 = 20.0;
+// a challenger needs this much more influence than the incumbent to flip a province
+pub const CONTEST_MARGIN: f32 = 1.25;
+
+pub type InfluenceMap = HashMap<ProvinceId, HashMap<PolityId, f32>>;
+
+pub fn settlement_influence(settlement: &Settlement, world: &World) -> f32 {
+    settlement.population(world) as f32 * settlement.level.rating() / SettlementLevel::Village.rating()
+}
+
+// equal claims go to the polity with more settlements, then to the older one
+fn tie_break(polity: &PolityId, sizes: &HashMap<PolityId, usize>) -> (usize, Reverse<usize>) {
+    (sizes.get(polity).copied().unwrap_or(0), Reverse(polity.num()))
+}
+
+// settled provinces belong to whoever holds the most people there
+pub fn settled_control(province: &Province, sizes: &HashMap<PolityId, usize>, world: &World) -> Option<PolityId> {
+    let mut populations: HashMap<PolityId, isize> = HashMap::new();
+    for settlement in province.settlements.iter() {
+        *populations
+            .entry(settlement.get().controller.clone())
+            .or_insert(0) += settlement.get().population(world);
+    }
+    populations
+        .into_iter()
+        .max_by_key(|(polity, population)| (*population, tie_break(polity, sizes)))
+        .map(|(polity, _)| polity)
+}
+
+pub fn project_influence(world: &World) -> InfluenceMap {
+    let mut influence: InfluenceMap = HashMap::new();
+    for settlement_id in world.iter_storage::<Settlement>() {
+        let settlement = settlement_id.get();
+        let strength = settlement_influence(&settlement, world);
+        let origin = settlement.province.get().coordinate;
+        for coordinate in origin.neighbors_in_radius_iter(INFLUENCE_RADIUS) {
+            if let Some(province) = world.get_province_coordinate(coordinate) {
+                if province.get().terrain == Terrain::Ocean {
+                    continue;
+                }
+                let falloff = (1 + origin.dist(coordinate)) as f32;
+                *influence
+                    .entry(province)
+                    .or_default()
+                    .entry(settlement.controller.clone())
+                    .or_insert(0.0) += strength / (falloff * falloff);
+            }
+        }
+    }
+    influence
+}
+
+// incumbents hold on unless a challenger clearly outweighs them
+pub fn resolve_contested(
+    incumbent: Option<&PolityId>,
+    claims: &HashMap<PolityId, f32>,
+    sizes: &HashMap<PolityId, usize>,
+) -> Option<PolityId> {
+    let (strongest, &strength) = claims
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1).then_with(|| tie_break(a.0, sizes).cmp(&tie_break(b.0, sizes))))?;
+    if strength < MIN_CLAIM_INFLUENCE {
+        return None;
+    }
+    let incumbent_strength = incumbent
+        .and_then(|polity| claims.get(polity))
+        .copied()
+        .unwrap_or(0.0);
+    if incumbent_strength >= MIN_CLAIM_INFLUENCE && strength < incumbent_strength * CONTEST_MARGIN {
+        incumbent.cloned()
+    } else {
+        Some(strongest.clone())
+    }
+}
+
+// which polities share a border with which
+pub fn polity_borders(world: &World) -> HashMap<PolityId, HashSet<PolityId>> {
+    let mut borders: HashMap<PolityId, HashSet<PolityId>> = HashMap::new();
+    for province in world.iter_storage::<Province>() {
+        let controller = match &province.get().controller {
+            Some(controller) => controller.clone(),
+            None => continue,
+        };
+        for neighbor in world.neighbors(province) {
+            if let Some(other) = &neighbor.get().controller {
+                if *other != controller {
+                    borders.entry(controller.clone()).or_default().insert(other.clone());
+                }
+            }
+        }
+    }
+    borders
+}
+
+pub struct UpdateTerritoryCommand;
+
+impl Command for UpdateTerritoryCommand {
+    fn run(&self, world: &mut World) {
+        let influence = project_influence(world);
+        let sizes = settlements_by_polity(world)
+            .into_iter()
+            .map(|(polity, settlements)| (polity, settlements.len()))
+            .collect::<HashMap<_, _>>();
+        for province in world.iter_storage::<Province>() {
+            if province.get().terrain == Terrain::Ocean {
+                continue;
+            }
+            let controller = if !province.get().settlements.is_empty() {
+                settled_control(&province.get(), &sizes, world)
+            } else if let Some(claims) = influence.get(province) {
+                resolve_contested(province.get().controller.as_ref(), claims, &sizes)
+            } else {
+                None
+            };
+            province.get_mut().controller = controller;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::*;
+
+// unrest from each harvest's tax per point of rate above what pops put up with
+pub const TAX_UNREST: f32 = 100.0;
+pub const TOLERATED_TAX_RATE: f32 = 0.05;
+// food value a levied soldier costs each month
+pub const ARMY_UPKEEP: f32 = 20.0;
+pub const WALLS_COST: f32 = 20000.0;
+// treasury a polity keeps before it starts handing out gifts
+pub const GIFT_THRESHOLD: f32 = 30000.0;
+pub const GIFT_FRACTION: f32 = 0.1;
+// value of gifts needed to buy one point of opinion
+pub const GIFT_VALUE_PER_OPINION: f32 = 200.0;
+
+pub fn polity_tax_rate(polity: &PolityId, world: &World) -> f32 {
+    let efficiency = (1.0 + polity.factor(world, FactorType::PolityStewardship)).max(0.1);
+    let mut rate = polity.get().level.tax_rate() * efficiency;
+    // wars have to be paid for somehow
+    if world.wars.iter().any(|war| war.attacker == *polity || war.defender == *polity) {
+        rate *= 2.0;
+    }
+    rate
+}
+
+pub fn treasury_spending(world: &World) {
+    let settlements = settlements_by_polity(world);
+    let borders = polity_borders(world);
+    let population = |polity: &PolityId| -> isize {
+        settlements
+            .get(polity)
+            .map(|ss| ss.iter().map(|s| s.get().population(world)).sum())
+            .unwrap_or(0)
+    };
+    for polity_id in world.iter_storage::<Polity>() {
+        let polity = polity_id.get();
+        let wealth = polity.treasury.value();
+        if let Some(capital) = &polity.capital {
+            if wealth > WALLS_COST * 1.5 && !capital.get().buildings.contains(&Building::Walls) {
+                world.add_command(Box::new(ConstructBuildingCommand {
+                    polity: polity_id.clone(),
+                    settlement: capital.clone(),
+                    building: Building::Walls,
+                }));
+                continue;
+            }
+        }
+        if wealth > TEMPLE_COST * 2.0 {
+            // temples go where the state faith is weakest
+            let unfaithful = |s: &SettlementId| -> isize {
+                s.get().pops.iter().filter(|p| p.get().religion != polity.religion).map(|p| p.get().size).sum()
+            };
+            let site = settlements.get(polity_id).and_then(|ss| {
+                ss.iter()
+                    .filter(|s| !s.get().buildings.contains(&Building::Temple))
+                    .filter(|s| unfaithful(s) > 0 || polity.capital.as_ref() == Some(*s))
+                    .max_by_key(|s| (unfaithful(s), s.get().population(world)))
+            });
+            if let Some(site) = site {
+                world.add_command(Box::new(ConstructBuildingCommand {
+                    polity: polity_id.clone(),
+                    settlement: site.clone(),
+                    building: Building::Temple,
+                }));
+                continue;
+            }
+        }
+        if wealth > GIFT_THRESHOLD {
+            // keep the big neighbors sweet
+            let own_population = population(polity_id);
+            let recipient = borders.get(polity_id).and_then(|neighbors| {
+                neighbors
+                    .iter()
+                    .filter(|n| population(n) > own_population)
+                    .min_by(|a, b| {
+                        world.diplomacy.opinion(a, polity_id)
+                            .partial_cmp(&world.diplomacy.opinion(b, polity_id))
+                            .unwrap()
+                    })
+            });
+            if let Some(recipient) = recipient {
+                world.add_command(Box::new(SendGiftCommand {
+                    from: polity_id.clone(),
+                    to: recipient.clone(),
+                }));
+            }
+        }
+    }
+}
+
+pub struct CollectTaxCommand(pub PopId);
+
+impl Command for CollectTaxCommand {
+    fn run(&self, world: &mut World) {
+        let polity = self.0.get().polity.clone();
+        if !world.contains(&polity) {
+            return;
+        }
+        let rate = polity_tax_rate(&polity, world);
+        let mut collected = GoodStorage(HashMap::new());
+        {
+            let mut pop = self.0.get_mut();
+            let size = pop.size as f32;
+            let mut taken_value = 0.0;
+            for (good, amount) in pop.owned_goods.0.iter_mut() {
+                // a year of eating is spoken for, everything else is surplus
+                let needed = good.max_consumed_monthly_per_capita() * size * 12.0;
+                let surplus = (*amount - needed).max(0.0);
+                let taken = (surplus * rate).min(surplus);
+                taken_value += taken * good.value();
+                *amount -= taken;
+                collected.add(*good, taken);
+            }
+            // only pops that actually had something taken resent it
+            if taken_value > 0.0 {
+                pop.unrest = (pop.unrest + TAX_UNREST * (rate - TOLERATED_TAX_RATE).max(0.0)).min(100.0);
+            }
+        }
+        polity.get_mut().treasury.absorb(collected);
+    }
+}
+
+pub struct ConstructBuildingCommand {
+    pub polity: PolityId,
+    pub settlement: SettlementId,
+    pub building: Building,
+}
+
+impl Command for ConstructBuildingCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.settlement) || !self.polity.get_mut().treasury.spend_value(self.building.cost()) {
+            return;
+        }
+        self.settlement.get_mut().buildings.insert(self.building);
+        world.events.add(Rc::new(BuildingConstructedEvent {
+            polity: self.polity.clone(),
+            settlement: self.settlement.clone(),
+            building: self.building,
+        }));
+    }
+}
+
+pub struct SendGiftCommand {
+    pub from: PolityId,
+    pub to: PolityId,
+}
+
+impl Command for SendGiftCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.to) {
+            return;
+        }
+        let gift = self.from.get_mut().treasury.take_fraction(GIFT_FRACTION);
+        let value = gift.value();
+        self.to.get_mut().treasury.absorb(gift);
+        world.diplomacy.add_opinion(&self.to, &self.from, (value / GIFT_VALUE_PER_OPINION).min(30.0));
+        world.events.add(Rc::new(GiftSentEvent {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            value,
+        }));
+    }
+}
@@ -255,7 +255,7 @@ impl Container for DateContainer {
     }
 
     fn layout(&mut self, ctx: &mut Context, constraints: Constraints, world: &World) {
-        self.0.text = new_text(format!("{:?}", world.date));
+        self.0.text = new_text(world.date.format(DateFormat::Long));
         self.0.layout(ctx, constraints, world)
     }
 }
@@ -230,7 +230,13 @@ impl InfoPanelBuilder for ProvinceInfoBuilder {
             DateContainer::new(),
             infotainer!(self.0, terrain),
             infotainer!(self.0, coordinate),
+            self.0.info_container(|province, w| {
+                let province = province.get();
+                let regions = province.area.iter().chain(province.landmass.iter()).chain(province.sea_zones.iter());
+                regions.map(|r| r.get().name.clone()).collect::<Vec<String>>().join(", ")
+            }),
             infotainer!(self.0, coastal),
+            self.0.info_container(|province, w| province.get().deposits.iter().map(|g| format!("{:?}", g)).collect::<Vec<String>>().join(", ")),
             province_controller(self.0.clone()),
             province_population(self.0.clone()),
             settlement_list,
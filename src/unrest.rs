@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use rand::thread_rng;
+
+use crate::*;
+
+// monthly unrest from going hungry, scaled by how far short of a full diet
+pub const HUNGER_UNREST: f32 = 8.0;
+pub const FOREIGN_RULE_UNREST: f32 = 2.0;
+pub const FOREIGN_RELIGION_UNREST: f32 = 1.5;
+// distance from the capital before rule starts to feel remote
+pub const LOCAL_RADIUS: isize = 2;
+// monthly unrest per province beyond the local radius, before cohesion
+pub const DISTANT_RULE_UNREST: f32 = 2.0;
+// a fed pop slowly forgets
+pub const UNREST_DECAY: f32 = 0.95;
+// settlement average unrest at which revolt becomes possible
+pub const REVOLT_UNREST: f32 = 40.0;
+// yearly chance of a settlement at the threshold rising up
+pub const REVOLT_CHANCE: f64 = 0.25;
+
+pub fn settlement_unrest(settlement: &SettlementId) -> f32 {
+    let settlement = settlement.get();
+    let mut total = 0.0;
+    let mut size = 0;
+    for pop in settlement.pops.iter() {
+        total += pop.get().unrest * pop.get().size as f32;
+        size += pop.get().size;
+    }
+    if size == 0 {
+        0.0
+    } else {
+        total / size as f32
+    }
+}
+
+pub fn revolts(world: &World) {
+    let mut rising: HashSet<PolityId> = HashSet::new();
+    for settlement in world.iter_storage::<Settlement>() {
+        let controller = settlement.get().controller.clone();
+        // one uprising per polity per year, the rest can join it
+        if rising.contains(&controller) || controller.get().capital.as_ref() == Some(settlement) {
+            continue;
+        }
+        let unrest = settlement_unrest(settlement);
+        if unrest >= REVOLT_UNREST && individual_event(REVOLT_CHANCE * (unrest / REVOLT_UNREST) as f64) {
+            rising.insert(controller);
+            world.add_command(Box::new(RevoltCommand(settlement.clone())));
+        }
+    }
+}
+
+pub struct UpdateUnrestCommand(pub PopId);
+
+impl Command for UpdateUnrestCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.0) {
+            return;
+        }
+        let mut pop = self.0.get_mut();
+        if !world.contains(&pop.polity) {
+            return;
+        }
+        // pops eat before this runs, so satiety is this month's
+        let hunger = 1.0 - (pop.satiety.base / 2500.0).min(1.0);
+        let mut grievance = HUNGER_UNREST * hunger;
+        let polity_id = pop.polity.clone();
+        let polity = polity_id.get();
+        if let Some(capital) = &polity.capital {
+            let distance = capital.get().province.get().coordinate.dist(pop.province.get().coordinate);
+            let remoteness = (distance - LOCAL_RADIUS).max(0) as f32;
+            grievance += DISTANT_RULE_UNREST * remoteness * (1.0 - polity.level.cohesion());
+        }
+        let polity_culture = polity.primary_culture.clone();
+        if pop.culture != polity_culture {
+            grievance += FOREIGN_RULE_UNREST * (1.0 - culture_affinity(&pop.culture, &polity_culture));
+        }
+        if pop.religion != polity.religion {
+            let tolerance = if polity.religion.get().has_doctrine(Doctrine::Tolerant) { 0.5 } else { 1.0 };
+            grievance += FOREIGN_RELIGION_UNREST * tolerance;
+        }
+        // good rulers soothe grievances, bad ones make them worse
+        let stability = polity_id.factor(world, FactorType::PolityStability)
+            + pop.settlement.factor(world, FactorType::SettlementStability);
+        grievance *= (1.0 - stability).max(0.1);
+        pop.unrest = (pop.unrest * UNREST_DECAY + grievance).min(100.0);
+    }
+}
+
+pub struct RevoltCommand(pub SettlementId);
+
+impl Command for RevoltCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.0) {
+            return;
+        }
+        let former = self.0.get().controller.clone();
+        let culture = self.0.get().primary_culture.clone();
+        let name = culture.get().language.get().generate_toponym();
+        let rebels = add_polity(world, name, culture, PolityLevel::Tribe, &mut thread_rng());
+        // disgruntled settlements nearby throw in with the rebels
+        let province = self.0.get().province.clone();
+        let mut seized = vec![self.0.clone()];
+        for settlement in former.get().settlements(world) {
+            if settlement == self.0 || former.get().capital.as_ref() == Some(&settlement) {
+                continue;
+            }
+            let nearby = settlement.get().province.get().coordinate.dist(province.get().coordinate) <= 1;
+            if nearby && settlement_unrest(&settlement) >= REVOLT_UNREST / 2.0 {
+                seized.push(settlement);
+            }
+        }
+        for settlement in seized.iter() {
+            settlement.get_mut().controller = rebels.clone();
+            for pop in settlement.get().pops.iter() {
+                pop.get_mut().polity = rebels.clone();
+                // the rising itself lets off steam
+                pop.get_mut().unrest *= 0.5;
+            }
+        }
+        rebels.get_mut().capital = Some(self.0.clone());
+        let leader = rebels.get().leader.clone();
+        SetHeadmanCommand {
+            settlement: self.0.clone(),
+            headman: leader,
+        }
+        .run(world);
+        world.diplomacy.add_opinion(&former, &rebels, -60.0);
+        world.events.add(Rc::new(RevoltEvent {
+            rebels: rebels.clone(),
+            former: former.clone(),
+            settlements: seized,
+        }));
+        if former.get().settlements(world).is_empty() {
+            DissolvePolityCommand(former).run(world);
+        } else {
+            // the old rulers want it back
+            DeclareWarCommand {
+                attacker: former,
+                defender: rebels,
+                objective: self.0.clone(),
+            }
+            .run(world);
+        }
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::HashSet;
+
+use crate::*;
+
+// share of a pop that answers the call to arms
+pub const LEVY_RATE: f32 = 0.15;
+pub const WARRIOR_LEVY_RATE: f32 = 0.25;
+// yearly chance a polity picks a fight with a weaker neighbor
+pub const WAR_CHANCE: f64 = 0.01;
+// days of besieging an evenly matched garrison before it falls
+pub const SIEGE_DAYS: f32 = 90.0;
+// campaigns that drag on longer than this end in a white peace
+pub const MAX_CAMPAIGN_DAYS: isize = 720;
+// how much further an army will march to take a mine
+pub const MINE_LURE: isize = 4;
+// how close to their objective invaders get before the defenders go out to meet them
+pub const INTERCEPT_RANGE: isize = 3;
+
+pub struct War {
+    pub attacker: PolityId,
+    pub defender: PolityId,
+    pub objective: SettlementId,
+    pub start: Date,
+}
+
+impl World {
+    pub fn at_war(&self, a: &PolityId, b: &PolityId) -> bool {
+        self.wars.iter().any(|war| {
+            (war.attacker == *a && war.defender == *b) || (war.attacker == *b && war.defender == *a)
+        })
+    }
+}
+
+#[derive(IronData)]
+pub struct Army {
+    pub id: usize,
+    pub polity: PolityId,
+    pub leader: CharacterId,
+    pub levies: Vec<(PopId, isize)>,
+    pub province: ProvinceId,
+    pub objective: SettlementId,
+    // fighting for the objective's owner rather than against it
+    pub defending: bool,
+    pub raised: Date,
+    pub march_progress: f32,
+    pub siege_progress: f32,
+    // provinces still to march through, the next one last
+    pub route: Vec<Coordinate>,
+}
+
+gen_id!(Army, ArmyId);
+
+impl Army {
+    pub fn size(&self) -> isize {
+        self.levies
+            .iter()
+            .map(|(pop, levied)| (*levied).min(pop.get().size))
+            .sum()
+    }
+
+    pub fn strength(&self, world: &World) -> f32 {
+        let mut strength = self.size() as f32 * self.leader.get().martial_modifier();
+        if self.polity.get().primary_culture.get().features.contains(&CultureFeature::Warrior) {
+            strength *= 1.25;
+        }
+        if self.defending {
+            strength *= self.province.get().terrain.defense();
+        }
+        strength
+    }
+
+    // deserters go home alive, unlike casualties
+    pub fn desert(&mut self, deserters: isize) {
+        let size = self.size();
+        if size == 0 {
+            return;
+        }
+        for (_, levied) in self.levies.iter_mut() {
+            *levied -= (deserters * *levied / size).min(*levied);
+        }
+    }
+
+    // casualties are spread over the levied pops and come straight out of their numbers
+    pub fn take_casualties(&mut self, casualties: isize) -> isize {
+        let size = self.size();
+        if size == 0 {
+            return 0;
+        }
+        let mut total = 0;
+        for (pop, levied) in self.levies.iter_mut() {
+            let share = (casualties * *levied / size).min(*levied);
+            let dead = pop.get_mut().die(share);
+            *levied -= dead;
+            total += dead;
+        }
+        total
+    }
+}
+
+// pops bled white in the field are gone for good, and the levy rolls forget them
+pub fn bury_the_dead(army: &ArmyId, world: &mut World) {
+    let dead = army
+        .get()
+        .levies
+        .iter()
+        .filter(|(pop, _)| pop.get().size <= 0 && world.contains(pop))
+        .map(|(pop, _)| pop.clone())
+        .collect::<Vec<_>>();
+    for pop in dead {
+        DestroyPopCommand(pop).run(world);
+    }
+    army
+        .get_mut()
+        .levies
+        .retain(|(pop, levied)| *levied > 0 && world.contains(pop));
+}
+
+// defenders ride out to meet invaders closing in on the objective
+fn intercept_target(army: &ArmyId, world: &World) -> Option<Coordinate> {
+    let objective = army.get().objective.get().province.get().coordinate;
+    let polity = army.get().polity.clone();
+    world
+        .iter_storage::<Army>()
+        .filter(|other| {
+            let other = other.get();
+            !other.defending && other.objective == army.get().objective && world.at_war(&other.polity, &polity)
+        })
+        .map(|other| other.get().province.get().coordinate)
+        .filter(|coordinate| coordinate.dist(objective) <= INTERCEPT_RANGE)
+        .min_by_key(|coordinate| (coordinate.dist(objective), coordinate.x, coordinate.y))
+}
+
+pub fn raise_levies(polity: &PolityId, world: &World) -> Vec<(PopId, isize)> {
+    let levy_rate = if polity.get().primary_culture.get().features.contains(&CultureFeature::Warrior) {
+        WARRIOR_LEVY_RATE
+    } else {
+        LEVY_RATE
+    };
+    let mut levies = Vec::new();
+    let mut settlements = polity.get().settlements(world);
+    // vassals answer their overlord's call
+    for vassal in world.diplomacy.vassals(polity) {
+        settlements.extend(vassal.get().settlements(world));
+    }
+    for settlement in settlements {
+        for pop in settlement.get().pops.iter() {
+            let levied = (pop.get().size as f32 * levy_rate) as isize;
+            if levied > 0 {
+                levies.push((pop.clone(), levied));
+            }
+        }
+    }
+    levies
+}
+
+pub fn war_declarations(world: &World) {
+    let settlements = settlements_by_polity(world);
+    let borders = polity_borders(world);
+    let population = |polity: &PolityId| -> isize {
+        settlements
+            .get(polity)
+            .map(|ss| ss.iter().map(|s| s.get().population(world)).sum())
+            .unwrap_or(0)
+    };
+    // allies and vassals make a target look a lot less tempting
+    let defended_population = |polity: &PolityId| -> isize {
+        population(polity)
+            + world
+                .diplomacy
+                .allies(polity)
+                .iter()
+                .chain(world.diplomacy.vassals(polity).iter())
+                .map(&population)
+                .sum::<isize>()
+    };
+    let mut involved: HashSet<PolityId> = world
+        .wars
+        .iter()
+        .flat_map(|war| vec![war.attacker.clone(), war.defender.clone()])
+        .collect();
+    for polity_id in world.iter_storage::<Polity>() {
+        // subjects leave the fighting to their overlords
+        if involved.contains(polity_id) || world.diplomacy.overlord(polity_id).is_some() {
+            continue;
+        }
+        let polity = polity_id.get();
+        let capital = match &polity.capital {
+            Some(capital) => capital.get().province.get().coordinate,
+            None => continue,
+        };
+        let own_population = population(polity_id);
+        let target = borders.get(polity_id).and_then(|neighbors| {
+            neighbors
+                .iter()
+                .filter(|n| {
+                    !involved.contains(*n)
+                        && world.diplomacy.treaty_between(polity_id, n).is_none()
+                        && defended_population(n) < own_population
+                })
+                .min_by_key(|n| population(n))
+        });
+        let target = match target {
+            Some(target) => target.clone(),
+            None => continue,
+        };
+        let mut chance = WAR_CHANCE * (1.0 + polity_id.factor(world, FactorType::PolityAggression)).max(0.1) as f64;
+        if polity.primary_culture.get().features.contains(&CultureFeature::Warrior) {
+            chance *= 2.0;
+        }
+        if !individual_event(chance) {
+            continue;
+        }
+        let objective = settlements.get(&target).and_then(|ss| {
+            ss.iter()
+                .min_by_key(|s| {
+                    let settlement = s.get();
+                    let mines = settlement.features.iter().filter(|f| matches!(f, SettlementFeature::Mines(_))).count() as isize;
+                    let dist = settlement.province.get().coordinate.dist(capital);
+                    dist - mines * MINE_LURE
+                })
+                .cloned()
+        });
+        if let Some(objective) = objective {
+            world.add_command(Box::new(DeclareWarCommand {
+                attacker: polity_id.clone(),
+                defender: target.clone(),
+                objective,
+            }));
+            involved.insert(polity_id.clone());
+            involved.insert(target);
+        }
+    }
+}
+
+pub fn army_tick(world: &World) {
+    for army in world.iter_storage::<Army>() {
+        world.add_command(Box::new(UpdateArmyCommand(army.clone())));
+    }
+}
+
+pub struct DeclareWarCommand {
+    pub attacker: PolityId,
+    pub defender: PolityId,
+    pub objective: SettlementId,
+}
+
+impl Command for DeclareWarCommand {
+    fn run(&self, world: &mut World) {
+        if world.at_war(&self.attacker, &self.defender) {
+            return;
+        }
+        let attacker_home = match &self.attacker.get().capital {
+            Some(capital) => capital.get().province.clone(),
+            None => return,
+        };
+        // allies not already busy with a war of their own come to the defender's aid
+        let busy = |polity: &PolityId| world.wars.iter().any(|war| war.attacker == *polity || war.defender == *polity);
+        let allies = world
+            .diplomacy
+            .allies(&self.defender)
+            .into_iter()
+            .filter(|ally| *ally != self.attacker && !busy(ally))
+            .filter_map(|ally| {
+                let home = ally.get().capital.as_ref()?.get().province.clone();
+                Some((ally, home))
+            })
+            .collect::<Vec<_>>();
+        let mut sides = vec![
+            (self.attacker.clone(), self.attacker.get().ruler(), attacker_home, false),
+            (
+                self.defender.clone(),
+                self.objective.get().headman.clone(),
+                self.objective.get().province.clone(),
+                true,
+            ),
+        ];
+        for (ally, home) in allies.iter() {
+            sides.push((ally.clone(), ally.get().ruler(), home.clone(), true));
+        }
+        for (polity, _, _, defending) in sides.iter() {
+            if *defending {
+                world.wars.push(War {
+                    attacker: self.attacker.clone(),
+                    defender: polity.clone(),
+                    objective: self.objective.clone(),
+                    start: world.date,
+                });
+            }
+        }
+        for (polity, leader, province, defending) in sides {
+            let levies = raise_levies(&polity, world);
+            world.insert(Army {
+                id: 0,
+                polity,
+                leader,
+                levies,
+                province,
+                objective: self.objective.clone(),
+                defending,
+                raised: world.date,
+                march_progress: 0.0,
+                siege_progress: 0.0,
+                route: Vec::new(),
+            });
+        }
+        world.diplomacy.add_opinion(&self.defender, &self.attacker, -50.0);
+        for ally in world.diplomacy.allies(&self.defender) {
+            world.diplomacy.add_opinion(&ally, &self.attacker, -25.0);
+        }
+        world.events.add(Rc::new(WarDeclaredEvent {
+            attacker: self.attacker.clone(),
+            defender: self.defender.clone(),
+            objective: self.objective.clone(),
+        }));
+        for (ally, _) in allies {
+            world.events.add(Rc::new(AllyJoinedWarEvent {
+                ally,
+                defender: self.defender.clone(),
+                attacker: self.attacker.clone(),
+            }));
+        }
+    }
+}
+
+pub struct UpdateArmyCommand(pub ArmyId);
+
+impl UpdateArmyCommand {
+    fn march(&self, target: Coordinate, world: &mut World) {
+        let here = self.0.get().province.clone();
+        self.0.get_mut().march_progress += 1.0;
+        let days = match here.get().terrain {
+            Terrain::Ocean => SEA_MOVEMENT_COST,
+            terrain => terrain.movement_cost(),
+        };
+        if self.0.get().march_progress < days {
+            return;
+        }
+        // the cached route may have gone stale or lead somewhere else, so the next step has to still be open
+        let start = here.get().coordinate;
+        let next = self.0.get().route.last().and_then(|c| world.get_province_coordinate(*c));
+        let open = self.0.get().route.first() == Some(&target)
+            && next.is_some_and(|next| {
+                world.neighbors(&here).contains(&next) && LandAndSea.step(world, &here.get(), &next.get()).is_some()
+            });
+        if !open {
+            // overseas objectives are reached by boat
+            let path = find_path(world, start, target, &LandMovement).or_else(|| find_path(world, start, target, &LandAndSea));
+            match path {
+                Some(path) => self.0.get_mut().route = path.steps.into_iter().skip(1).rev().collect(),
+                None => {
+                    // no way there at all, so this army goes home
+                    self.disband(world);
+                    return;
+                }
+            }
+        }
+        let next = self.0.get_mut().route.pop().and_then(|c| world.get_province_coordinate(c));
+        let mut army = self.0.get_mut();
+        if let Some(next) = next {
+            army.province = next;
+        }
+        army.march_progress = 0.0;
+    }
+
+    // an invasion without invaders comes to nothing
+    fn disband(&self, world: &mut World) {
+        let objective = self.0.get().objective.clone();
+        let defending = self.0.get().defending;
+        world.remove(&self.0);
+        let invaders = world
+            .iter_storage::<Army>()
+            .any(|army| army.get().objective == objective && !army.get().defending);
+        if !defending && !invaders {
+            EndWarCommand { objective, victor: None }.run(world);
+        }
+    }
+
+    fn besiege(&self, world: &mut World) {
+        let objective = self.0.get().objective.clone();
+        let garrison = (objective.get().population(world) as f32 * LEVY_RATE).max(1.0);
+        let mut fortification = objective.get().level.rating() / SettlementLevel::Village.rating();
+        if objective.get().has_feature(SettlementFeature::Hilltop) {
+            fortification *= 2.0;
+        }
+        if objective.get().buildings.contains(&Building::Walls) {
+            fortification *= 1.5;
+        }
+        let progress = self.0.get().size() as f32 / (garrison * fortification * SIEGE_DAYS);
+        self.0.get_mut().siege_progress += progress;
+        if world.date.is_month() {
+            // disease and sallies wear down the besiegers
+            let attrition = self.0.get().size() / 50;
+            self.0.get_mut().take_casualties(attrition);
+            bury_the_dead(&self.0, world);
+        }
+        if self.0.get().siege_progress >= 1.0 && world.contains(&objective) {
+            let polity = self.0.get().polity.clone();
+            OccupySettlementCommand {
+                settlement: objective.clone(),
+                polity: polity.clone(),
+            }.run(world);
+            EndWarCommand {
+                objective,
+                victor: Some(polity),
+            }.run(world);
+        }
+    }
+}
+
+impl Command for UpdateArmyCommand {
+    fn run(&self, world: &mut World) {
+        if !world.contains(&self.0) {
+            return;
+        }
+        let objective = self.0.get().objective.clone();
+        let polity = self.0.get().polity.clone();
+        let campaign_days = self.0.get().raised.days_until(world.date);
+        if !world.contains(&objective) || !world.contains(&polity) || campaign_days > MAX_CAMPAIGN_DAYS {
+            EndWarCommand { objective, victor: None }.run(world);
+            return;
+        }
+        let leader = self.0.get().leader.clone();
+        if !leader.get().alive() {
+            // the ruler takes over from a fallen commander
+            self.0.get_mut().leader = polity.get().ruler();
+        }
+        if self.0.get().size() == 0 {
+            if self.0.get().defending {
+                self.disband(world);
+                return;
+            }
+            // nobody left to fight, the defenders carry the day
+            let victor = world
+                .wars
+                .iter()
+                .find(|war| war.objective == objective)
+                .map(|war| war.defender.clone());
+            EndWarCommand { objective, victor }.run(world);
+            return;
+        }
+        if world.date.is_month() {
+            let upkeep = self.0.get().size() as f32 * ARMY_UPKEEP;
+            let paid = polity.get_mut().treasury.spend_value(upkeep);
+            if !paid {
+                // unpaid levies drift home
+                let deserters = self.0.get().size() / 10 + 1;
+                self.0.get_mut().desert(deserters);
+            }
+        }
+        let province = self.0.get().province.clone();
+        let enemy = world
+            .iter_storage::<Army>()
+            .find(|other| {
+                **other != self.0
+                    && other.get().province == province
+                    && world.at_war(&other.get().polity, &polity)
+            })
+            .cloned();
+        if let Some(enemy) = enemy {
+            BattleCommand {
+                attacker: self.0.clone(),
+                defender: enemy,
+            }.run(world);
+        } else if self.0.get().defending {
+            let home = objective.get().province.get().coordinate;
+            let target = intercept_target(&self.0, world).unwrap_or(home);
+            if province.get().coordinate != target {
+                self.march(target, world);
+            }
+        } else if province == objective.get().province {
+            self.besiege(world);
+        } else {
+            let target = objective.get().province.get().coordinate;
+            self.march(target, world);
+        }
+    }
+}
+
+pub struct BattleCommand {
+    pub attacker: ArmyId,
+    pub defender: ArmyId,
+}
+
+impl Command for BattleCommand {
+    fn run(&self, world: &mut World) {
+        let attack = self.attacker.get().strength(world) * dev_mean_sample(0.2, 1.0).max(0.1) as f32;
+        let defense = self.defender.get().strength(world) * dev_mean_sample(0.2, 1.0).max(0.1) as f32;
+        let (winner, loser, ratio) = if attack > defense {
+            (self.attacker.clone(), self.defender.clone(), defense / attack)
+        } else {
+            (self.defender.clone(), self.attacker.clone(), attack / defense.max(1.0))
+        };
+        let winner_casualties = (winner.get().size() as f32 * 0.1 * ratio) as isize;
+        let loser_casualties = (loser.get().size() as f32 * (0.3 + 0.2 * (1.0 - ratio))) as isize;
+        let winner_losses = winner.get_mut().take_casualties(winner_casualties);
+        let loser_losses = loser.get_mut().take_casualties(loser_casualties);
+        // battles make and break the men who lead them
+        let winner_leader = winner.get().leader.clone();
+        let loser_leader = loser.get().leader.clone();
+        winner_leader.get_mut().prestige += 10.0;
+        loser_leader.get_mut().prestige -= 5.0;
+        let winner_culture = winner.get().polity.get().primary_culture.clone();
+        winner_culture.get_mut().martial_history += 1.0;
+        for (leader, chance) in [(&winner_leader, 0.01), (&loser_leader, 0.05)] {
+            if individual_event(chance) {
+                KillCharacterCommand(leader.clone(), CauseOfDeath::Battle).run(world);
+            }
+        }
+        for (character, feature, chance) in [
+            (&winner_leader, CharacterFeature::Strategist, 0.1),
+            (&winner_leader, CharacterFeature::Brave, 0.1),
+            (&loser_leader, CharacterFeature::Wounded, 0.2),
+            (&loser_leader, CharacterFeature::Coward, 0.1),
+        ] {
+            if individual_event(chance) {
+                GainTraitCommand {
+                    character: character.clone(),
+                    feature,
+                }
+                .run(world);
+            }
+        }
+        let province = self.attacker.get().province.clone();
+        world.events.add(Rc::new(BattleEvent {
+            winner: winner.get().polity.clone(),
+            loser: loser.get().polity.clone(),
+            province,
+            winner_losses,
+            loser_losses,
+        }));
+        bury_the_dead(&winner, world);
+        bury_the_dead(&loser, world);
+        if !loser.get().defending {
+            // a beaten attacker goes home and the war is over
+            let objective = loser.get().objective.clone();
+            let victor = winner.get().polity.clone();
+            EndWarCommand {
+                objective,
+                victor: Some(victor),
+            }.run(world);
+        } else {
+            // the routed defenders scatter, leaving the settlement to its walls
+            world.remove(&loser);
+        }
+    }
+}
+
+pub struct OccupySettlementCommand {
+    pub settlement: SettlementId,
+    pub polity: PolityId,
+}
+
+impl Command for OccupySettlementCommand {
+    fn run(&self, world: &mut World) {
+        let previous = self.settlement.get().controller.clone();
+        self.settlement.get_mut().controller = self.polity.clone();
+        for pop in self.settlement.get().pops.iter() {
+            pop.get_mut().polity = self.polity.clone();
+        }
+        world.diplomacy.add_opinion(&previous, &self.polity, -30.0);
+        world.events.add(Rc::new(SettlementOccupiedEvent {
+            settlement: self.settlement.clone(),
+            occupier: self.polity.clone(),
+            previous: previous.clone(),
+        }));
+        let was_capital = previous.get().capital.as_ref() == Some(&self.settlement);
+        if was_capital {
+            RelocateCapitalCommand(previous).run(world);
+        }
+    }
+}
+
+pub struct EndWarCommand {
+    pub objective: SettlementId,
+    pub victor: Option<PolityId>,
+}
+
+impl Command for EndWarCommand {
+    fn run(&self, world: &mut World) {
+        let armies = world
+            .iter_storage::<Army>()
+            .filter(|army| army.get().objective == self.objective)
+            .cloned()
+            .collect::<Vec<_>>();
+        for army in armies.iter() {
+            world.remove(army);
+        }
+        let (ended, wars): (Vec<_>, Vec<_>) = std::mem::take(&mut world.wars)
+            .into_iter()
+            .partition(|war| war.objective == self.objective);
+        world.wars = wars;
+        for war in ended {
+            world.events.add(Rc::new(WarEndedEvent {
+                attacker: war.attacker,
+                defender: war.defender,
+                victor: self.victor.clone(),
+            }));
+        }
+    }
+}
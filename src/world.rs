@@ -11,51 +11,10 @@ use rayon::prelude::*;
 
 use crate::*;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Date {
-    pub day: usize,
-}
-
-impl Date {
-    pub fn is_month(&self) -> bool {
-        self.day % 30 == 0
-    }
-
-    pub fn is_year(&self) -> bool {
-        self.day % 360 == 0
-    }
-
-    pub fn month(&self) -> usize {
-        (self.day / 30) % 12 + 1
-    }
-
-    pub fn year(&self) -> usize {
-        self.day / 360 + 1
-    }
-
-    pub fn day_of_month(&self) -> usize {
-        self.day % 30 + 1
-    }
-
-    pub fn age(&self, now: Date) -> usize {
-        (now.day - self.day) / 360
-    }
-
-    pub fn add_days(self, days: usize) -> Self {
-        Date { day: self.day + days }
-    }
-}
-
 pub fn parse_path(path: &'static str) {
     let path_regex = r"((self\.)?\w+)\.(.*)";
 }
 
-impl Debug for Date {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(format!("{:02}/{:02}/{}", self.month(), self.day_of_month(), self.year()).as_str())
-    }
-}
-
 pub struct World {
     pub date: Date,
     pub province_coord_map: HashMap<Coordinate, ProvinceId>,
@@ -67,6 +26,10 @@ pub struct World {
     pub logs: Logs,
     pub selected_province: Option<ProvinceId>,
     pub population: isize,
+    pub wars: Vec<War>,
+    pub diplomacy: Diplomacy,
+    pub map_size: (isize, isize),
+    pub adjacency: HashMap<ProvinceId, Vec<ProvinceId>>,
 }
 
 impl World {
@@ -105,6 +68,10 @@ impl World {
         self.province_coord_map.get(&coord).map(|p| p.clone())
     }
 
+    pub fn neighbors(&self, province: &ProvinceId) -> &[ProvinceId] {
+        self.adjacency.get(province).map(|n| n.as_slice()).unwrap_or(&[])
+    }
+
     pub fn insert_settlement(&mut self, settlement: Settlement) -> SettlementId {
         let set_id = self.insert::<Settlement>(settlement);
         set_id
@@ -123,6 +90,13 @@ impl World {
         self.storages.insert(data)
     }
 
+    pub fn contains<Id>(&self, id: &Id) -> bool
+    where
+        Id: IronId + Clone + 'static,
+    {
+        self.storages.get_storage::<Id::Target>().has_id(id)
+    }
+
     pub fn remove<Id>(&mut self, id: &Id)
     where
         Id: IronId + 'static,
@@ -131,6 +105,22 @@ impl World {
     }
 
     pub fn new(ctx: &mut Context) -> Self {
+        Self::default()
+    }
+
+    pub fn iter_storage<T>(&self) -> Values<'_, usize, T::IdType> where T: IronData + 'static {
+        self.storages.get_storage::<T>().id_map.values()
+    }
+
+    pub fn pixel_to_province(&self, pixel: Point2) -> Option<ProvinceId> {
+        let coord = Coordinate::from_pixel_pos(pixel, &self.camera);
+        self.get_province_coordinate(coord)
+    }
+}
+
+// nothing in the world needs a window, so tests can make one without a Context
+impl Default for World {
+    fn default() -> Self {
         Self {
             date: Date { day: 0 },
             province_coord_map: Default::default(),
@@ -142,22 +132,17 @@ impl World {
             logs: Default::default(),
             selected_province: Default::default(),
             population: 0,
+            wars: Vec::new(),
+            diplomacy: Default::default(),
+            map_size: (MAP_SIZE, MAP_SIZE),
+            adjacency: HashMap::new(),
             // ui_system: Default::default(),
         }
     }
-
-    pub fn iter_storage<T>(&self) -> Values<'_, usize, T::IdType> where T: IronData + 'static {
-        self.storages.get_storage::<T>().id_map.values()
-    }
-
-    pub fn pixel_to_province(&self, pixel: Point2) -> Option<ProvinceId> {
-        let coord = Coordinate::from_pixel_pos(pixel, &self.camera);
-        self.get_province_coordinate(coord)
-    }
 }
 
 fn random_place_name(culture: CultureId) -> String {
-    culture.get().language.get().generate_name(3)
+    culture.get().language.get().generate_toponym()
 }
 
 pub fn pops_yearly_growth(world: &World) {
@@ -181,6 +166,7 @@ pub fn harvest_provinces(world: &World) {
             for settlement in province.get().settlements.iter() {
                 for pop in settlement.get().pops.iter() {
                     harvest(pop, world);
+                    world.add_command(Box::new(CollectTaxCommand(pop.clone())));
                 }
             }
         }
@@ -188,23 +174,29 @@ pub fn harvest_provinces(world: &World) {
 }
 
 pub fn day_tick(world: &World) {
+    army_tick(world);
+    character_lifecycle(world);
     if world.date.is_year() {
         pops_yearly_growth(world);
-        for character in world.iter_storage::<Character>() {
-            if character.get().death.is_none() && character.get().birthday.age(world.date) as f32 > character.get().health {
-                // sic fortuna
-                world.events.add_deferred(
-                    Rc::new(CharacterDiedEvent(character.clone())),
-                    world.date.day + Uniform::from(1..360).sample(&mut thread_rng()),
-                );
-            }
-        }
+        polity_progression(world);
+        diplomacy_tick(world);
+        war_declarations(world);
+        treasury_spending(world);
+        revolts(world);
+        family_tick(world);
+        epidemics(world);
+        language_drift(world);
+        culture_tick(world);
+        religion_tick(world);
     }
 
     if world.date.is_month() {
+        update_ruler_factors(world);
         harvest_provinces(world);
+        world.add_command(Box::new(UpdateTerritoryCommand));
         for pop in world.iter_storage::<Pop>() {
             world.add_command(Box::new(PopEatCommand(pop.clone())));
+            world.add_command(Box::new(UpdateUnrestCommand(pop.clone())));
         }
     }
 }
@@ -1,69 +1,427 @@
-use std::{collections::{HashMap, HashSet}, f32::consts::PI};
+use std::collections::{HashMap, HashSet};
 
 use lazy_static::__Deref;
-use noise::{Fbm, HybridMulti, NoiseFn, Perlin};
-use rand::{Rng, random, thread_rng};
+use noise::{Fbm, HybridMulti, MultiFractal, NoiseFn, Perlin, Seedable};
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng, random, thread_rng};
 use rand_distr::Uniform;
 
 use crate::*;
 
 pub const MAP_SIZE: isize = 200;
+pub const WORLDGEN_PATH: &str = "worldgen.ini";
 
-fn center_dist(coordinate: Coordinate) -> f32 {
-    coordinate.dist(Coordinate::new(MAP_SIZE / 4, MAP_SIZE / 2)) as f32
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MapShape {
+    // a ring of land around a sea
+    InlandSea,
+    Continents,
+    Archipelago,
 }
 
-fn generate_height_map() -> HashMap<Coordinate, f32> {
+impl std::str::FromStr for MapShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inland_sea" => Ok(MapShape::InlandSea),
+            "continents" => Ok(MapShape::Continents),
+            "archipelago" => Ok(MapShape::Archipelago),
+            _ => Err(format!("unknown map shape '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldGenConfig {
+    // the same seed gives the same starting world, though not the same history after it
+    pub seed: u64,
+    pub width: isize,
+    pub height: isize,
+    pub shape: MapShape,
+    // raises or lowers every coast
+    pub sea_level: f32,
+    pub octaves: usize,
+    pub landmasses: usize,
+    pub rivers: usize,
+    pub cultures: usize,
+    pub religions: usize,
+    // chance that a land province starts with a settlement
+    pub settlement_density: f32,
+    // chance that a settlement starts as the seat of a chiefdom over its neighbors
+    pub chiefdom_chance: f32,
+    pub max_polity_size: usize,
+    pub pop_size: isize,
+    // negative years are before the epoch
+    pub start_year: isize,
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self {
+            seed: random(),
+            width: MAP_SIZE,
+            height: MAP_SIZE,
+            shape: MapShape::InlandSea,
+            sea_level: 0.0,
+            octaves: 6,
+            landmasses: 1,
+            rivers: 60,
+            cultures: 4,
+            religions: 4,
+            settlement_density: 0.1,
+            chiefdom_chance: 0.15,
+            max_polity_size: 8,
+            pop_size: 100,
+            start_year: 1,
+        }
+    }
+}
+
+impl WorldGenConfig {
+    pub fn preset(name: &str) -> Option<Self> {
+        let default = Self::default();
+        match name {
+            "inland_sea" => Some(default),
+            "continent" => Some(Self {
+                shape: MapShape::Continents,
+                sea_level: 0.1,
+                ..default
+            }),
+            "archipelago" => Some(Self {
+                shape: MapShape::Archipelago,
+                landmasses: 12,
+                rivers: 30,
+                cultures: 6,
+                religions: 3,
+                ..default
+            }),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "seed" => self.seed = parse_value(key, value)?,
+            "width" => self.width = parse_value(key, value)?,
+            "height" => self.height = parse_value(key, value)?,
+            "size" => {
+                self.width = parse_value(key, value)?;
+                self.height = self.width;
+            },
+            "shape" => self.shape = parse_value(key, value)?,
+            "sea_level" => self.sea_level = parse_value(key, value)?,
+            "octaves" => self.octaves = parse_value(key, value)?,
+            "landmasses" => self.landmasses = parse_value(key, value)?,
+            "rivers" => self.rivers = parse_value(key, value)?,
+            "cultures" => self.cultures = parse_value(key, value)?,
+            "religions" => self.religions = parse_value(key, value)?,
+            "settlement_density" => self.settlement_density = parse_value(key, value)?,
+            "chiefdom_chance" => self.chiefdom_chance = parse_value(key, value)?,
+            "max_polity_size" => self.max_polity_size = parse_value(key, value)?,
+            "pop_size" => self.pop_size = parse_value(key, value)?,
+            "start_year" => self.start_year = Date::parse_year(value).map_err(|e| format!("{} for {}", e, key))?,
+            _ => return Err(format!("unknown worldgen setting '{}'", key)),
+        }
+        Ok(())
+    }
+
+    // a preset section starts from the built in preset named by `base`, or its own name
+    pub fn from_ini(text: &str, preset: &str) -> Result<Self, String> {
+        let sections = parse_ini(text)?;
+        let section = match sections.iter().find(|s| s.name == preset) {
+            Some(section) => section,
+            None => return Self::preset(preset).ok_or_else(|| format!("no worldgen preset '{}'", preset)),
+        };
+        let base = section.get("base").unwrap_or(preset);
+        let mut config = Self::preset(base).unwrap_or_default();
+        for (key, value) in section.entries.iter().filter(|(k, _)| k != "base") {
+            config.set(key, value)?;
+        }
+        if config.width < 8 || config.height < 8 {
+            return Err(format!("map of {}x{} is too small", config.width, config.height));
+        }
+        config.religions = config.religions.max(1).min(config.cultures.max(1));
+        Ok(config)
+    }
+
+    pub fn load(path: &str, preset: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_ini(&text, preset).map_err(|e| format!("{}: {}", path, e)),
+            Err(_) => Self::preset(preset).ok_or_else(|| format!("no worldgen preset '{}'", preset)),
+        }
+    }
+}
+
+// distance from a point given as a share of the map, with the east-west axis weighted by `stretch`
+fn map_dist(coordinate: Coordinate, center: (f32, f32), stretch: f32, config: &WorldGenConfig) -> f32 {
+    let origin = Coordinate::new(0, 0).base_pixel_pos();
+    let p = coordinate.base_pixel_pos();
+    let width = TILE_SIZE_X * SQRT_3 * config.width as f32;
+    let height = TILE_SIZE_Y * 1.5 * config.height as f32;
+    let dx = (p.x - origin.x) / width - center.0;
+    let dy = (p.y - origin.y) / height - center.1;
+    ((stretch * dx).powi(2) + dy.powi(2)).sqrt()
+}
+
+fn bump(dist: f32, radius: f32) -> f32 {
+    0.8 * (1.0 - (dist / radius).powi(2)) - 0.2
+}
+
+fn base_height(coordinate: Coordinate, centers: &[(f32, f32)], config: &WorldGenConfig) -> f32 {
+    let height = match config.shape {
+        MapShape::InlandSea => {
+            let ring = map_dist(coordinate, (0.5, 0.5), 0.6, config) - 0.24;
+            bump(ring, 0.12)
+        },
+        MapShape::Continents | MapShape::Archipelago => {
+            let radius = if config.shape == MapShape::Continents { 0.35 } else { 0.25 };
+            let radius = radius / (centers.len() as f32).sqrt();
+            centers
+                .iter()
+                .map(|&center| bump(map_dist(coordinate, center, 1.0, config), radius))
+                .fold(-1.0, f32::max)
+        },
+    };
+    height.max(-1.0) - config.sea_level
+}
+
+fn generate_height_map(config: &WorldGenConfig, rng: &mut StdRng) -> HashMap<Coordinate, f32> {
     /*
-     * add perlin noise to basin
+     * add fractal noise to the landmasses of the map shape
      */
     let mut height_map: HashMap<Coordinate, f32> = HashMap::new();
-    let perlin = Perlin::new();
-    let fbm = Fbm::new();
-    for i in 0..MAP_SIZE {
-        for j in 0..MAP_SIZE {
-            let coordinate = Coordinate::new(i - (j / 2), j);
+    let fbm = Fbm::new().set_seed(config.seed as u32).set_octaves(config.octaves.max(1));
+    let centers = (0..config.landmasses.max(1))
+        .map(|_| (rng.gen_range(0.2..0.8), rng.gen_range(0.2..0.8)))
+        .collect::<Vec<_>>();
+    for i in 0..config.width {
+        for j in 0..config.height {
+            let coordinate = Coordinate::from_offset(i, j);
             let bpp = coordinate.base_pixel_pos();
-            let basin_height = (3.0 * PI * center_dist(coordinate) / MAP_SIZE as f32)
-                .sin()
-                .powf(3.0)
-                - 0.1;
             let noise = fbm.get([
                 bpp.x as f64 / (5.0 * TILE_SIZE_X as f64),
                 bpp.y as f64 / (5.0 * TILE_SIZE_Y as f64),
             ]) as f32;
-            height_map.insert(coordinate, noise + basin_height);
+            height_map.insert(coordinate, noise + base_height(coordinate, &centers, config));
         }
     }
     height_map
 }
 
-pub fn generate_world(world: &mut World) {
-    let height_map = generate_height_map();
-    let mut ocean_map: HashSet<Coordinate> = HashSet::new();
-    for (&coordinate, &height) in height_map.iter() {
-        let terrain = if height > 0.0 {
-            Terrain::Hills
-        } else {
-            Terrain::Ocean
-        };
-        let province_id = world.insert_province(Province {
+// share of land in each elevation band, highest first
+pub const MOUNTAIN_SHARE: f32 = 0.08;
+pub const HILL_SHARE: f32 = 0.2;
+pub const LOWLAND_SHARE: f32 = 0.3;
+
+fn land_threshold(height_map: &HashMap<Coordinate, f32>, share: f32) -> f32 {
+    let mut heights = height_map.values().filter(|h| **h > 0.0).cloned().collect::<Vec<_>>();
+    heights.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    let idx = ((heights.len() as f32 * share) as usize).min(heights.len().saturating_sub(1));
+    heights.get(idx).cloned().unwrap_or(0.0)
+}
+
+fn latitude(coordinate: Coordinate, config: &WorldGenConfig) -> f32 {
+    coordinate.y as f32 / config.height as f32
+}
+
+// colder to the north and up high
+fn generate_temperature(height_map: &HashMap<Coordinate, f32>, config: &WorldGenConfig) -> HashMap<Coordinate, f32> {
+    let perlin = Perlin::new().set_seed(config.seed.wrapping_add(1) as u32);
+    height_map
+        .iter()
+        .map(|(&coordinate, &height)| {
+            let bpp = coordinate.base_pixel_pos();
+            let noise = perlin.get([bpp.x as f64 / (20.0 * TILE_SIZE_X as f64), bpp.y as f64 / (20.0 * TILE_SIZE_Y as f64)]) as f32;
+            let temperature = 0.15 + 0.75 * latitude(coordinate, config) - 0.6 * height.max(0.0) + 0.1 * noise;
+            (coordinate, temperature.clamp(0.0, 1.0))
+        })
+        .collect()
+}
+
+// westerly winds pick up water over the sea and drop it on the slopes they climb
+fn generate_moisture(height_map: &HashMap<Coordinate, f32>, config: &WorldGenConfig) -> HashMap<Coordinate, f32> {
+    let mut moisture = HashMap::new();
+    for j in 0..config.height {
+        let mut carried: f32 = 1.0;
+        let mut last_height: f32 = 0.0;
+        for i in 0..config.width {
+            let coordinate = Coordinate::from_offset(i, j);
+            let height = height_map[&coordinate];
+            if height <= 0.0 {
+                carried = (carried + 0.1).min(1.0);
+                moisture.insert(coordinate, 1.0);
+            } else {
+                let rise = (height - last_height).max(0.0);
+                let rain = carried * (0.05 + 1.5 * rise).min(1.0);
+                // some of the rain evaporates again and moves on
+                carried = (carried - 0.6 * rain).max(0.0);
+                moisture.insert(coordinate, rain);
+            }
+            last_height = height.max(0.0);
+        }
+    }
+    let mut rainfall = moisture
+        .iter()
+        .filter(|(c, _)| height_map[*c] > 0.0)
+        .map(|(_, m)| *m)
+        .collect::<Vec<_>>();
+    rainfall.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let wet = rainfall.get(rainfall.len() * 9 / 10).cloned().unwrap_or(1.0).max(0.01);
+    // the south lies under dry subtropical air
+    for (coordinate, m) in moisture.iter_mut() {
+        let dryness = (1.0 - 2.0 * (latitude(*coordinate, config) - 0.5).max(0.0)).max(0.1);
+        *m = (*m / wet).min(1.0).sqrt() * dryness;
+    }
+    moisture
+}
+
+// rivers run downhill from wet highlands until they reach the sea,
+// pooling into a lake the first time they get stuck and spilling over its lowest rim
+fn trace_rivers(
+    height_map: &HashMap<Coordinate, f32>,
+    moisture: &HashMap<Coordinate, f32>,
+    source_height: f32,
+    config: &WorldGenConfig,
+    rng: &mut StdRng,
+) -> (HashMap<Coordinate, Coordinate>, HashSet<Coordinate>) {
+    let mut rivers = HashMap::new();
+    let mut lakes = HashSet::new();
+    let mut sources = height_map
+        .iter()
+        .filter(|(c, h)| **h >= source_height && moisture[*c] > 0.4)
+        .map(|(c, _)| *c)
+        .collect::<Vec<_>>();
+    sources.sort_by_key(|c| (c.y, c.x));
+    sources.shuffle(rng);
+    for source in sources.into_iter().take(config.rivers) {
+        let mut current = source;
+        let mut path = HashSet::new();
+        let mut pooled = false;
+        for _ in 0..(config.width + config.height) {
+            if rivers.contains_key(&current) || lakes.contains(&current) {
+                break;
+            }
+            path.insert(current);
+            let height = height_map[&current];
+            let lowest = current
+                .neighbors_iter()
+                .filter(|n| height_map.contains_key(n) && !path.contains(n))
+                .min_by(|a, b| height_map[a].partial_cmp(&height_map[b]).unwrap());
+            let next = match lowest {
+                Some(next) => next,
+                None => break,
+            };
+            if height_map[&next] >= height && !pooled {
+                lakes.insert(current);
+                pooled = true;
+                current = next;
+                continue;
+            }
+            rivers.insert(current, next);
+            if height_map[&next] <= 0.0 {
+                break;
+            }
+            current = next;
+        }
+    }
+    (rivers, lakes)
+}
+
+fn classify_biome(height: f32, temperature: f32, moisture: f32, thresholds: (f32, f32, f32)) -> (Terrain, Climate) {
+    let (mountains, hills, lowlands) = thresholds;
+    let climate = if temperature < 0.3 {
+        Climate::Cold
+    } else if temperature > 0.7 && moisture > 0.5 {
+        Climate::Tropical
+    } else if moisture < 0.35 && temperature > 0.5 {
+        Climate::Dry
+    } else {
+        Climate::Mild
+    };
+    let terrain = if height <= 0.0 {
+        Terrain::Ocean
+    } else if height >= mountains {
+        Terrain::Mountains
+    } else if height >= hills {
+        Terrain::Hills
+    } else if moisture < 0.25 && temperature > 0.6 {
+        Terrain::Desert
+    } else if moisture > 0.75 && height < lowlands {
+        Terrain::Marsh
+    } else if moisture > 0.55 {
+        Terrain::Forest
+    } else {
+        Terrain::Plains
+    };
+    (terrain, climate)
+}
+
+pub fn harvest_month(climate: Climate) -> usize {
+    match climate {
+        Climate::Tropical => 3,
+        Climate::Dry => 6,
+        Climate::Mild => 8,
+        Climate::Cold => 9,
+    }
+}
+
+pub fn generate_world(world: &mut World, config: &WorldGenConfig, rng: &mut StdRng) {
+    world.map_size = (config.width, config.height);
+    let height_map = generate_height_map(config, rng);
+    let temperature = generate_temperature(&height_map, config);
+    let mut moisture = generate_moisture(&height_map, config);
+    let thresholds = (
+        land_threshold(&height_map, MOUNTAIN_SHARE),
+        land_threshold(&height_map, MOUNTAIN_SHARE + HILL_SHARE),
+        land_threshold(&height_map, 1.0 - LOWLAND_SHARE),
+    );
+    let (rivers, lakes) = trace_rivers(&height_map, &moisture, thresholds.1, config, rng);
+    for coordinate in rivers.keys() {
+        if let Some(m) = moisture.get_mut(coordinate) {
+            *m = (*m + 0.2).min(1.0);
+        }
+    }
+    // in map order, so province ids come out the same for the same seed
+    let mut coordinates = height_map.keys().cloned().collect::<Vec<_>>();
+    coordinates.sort_by_key(|c| (c.y, c.x));
+    for coordinate in coordinates {
+        let height = height_map[&coordinate];
+        let (mut terrain, climate) = classify_biome(height, temperature[&coordinate], moisture[&coordinate], thresholds);
+        let river = rivers.get(&coordinate).cloned();
+        let mut features = HashSet::new();
+        if lakes.contains(&coordinate) {
+            terrain = Terrain::Ocean;
+            features.insert(ProvinceFeature::Lake);
+        } else if river.is_some() && (terrain == Terrain::Plains || terrain == Terrain::Marsh) {
+            features.insert(ProvinceFeature::Fertile);
+        } else if terrain == Terrain::Desert || terrain == Terrain::Mountains {
+            features.insert(ProvinceFeature::Infertile);
+        }
+        world.insert_province(Province {
             id: 0,
             terrain,
-            climate: Climate::Mild,
+            climate,
             coordinate,
-            harvest_month: 8,
+            harvest_month: harvest_month(climate),
             settlements: Vec::new(),
-            features: HashSet::new(),
+            features,
             controller: None,
             coastal: false,
+            river,
+            deposits: Vec::new(),
+            landmass: None,
+            area: None,
+            sea_zones: Vec::new(),
         });
-        if terrain == Terrain::Ocean {
-            ocean_map.insert(coordinate);
-        }
     }
-    for &coordinate in height_map.keys() {
-        let province_id = world.get_province_coordinate(coordinate).unwrap();
+    mark_coasts(world);
+    build_geography(world, config.seed);
+    place_deposits(world, rng);
+}
+
+pub fn mark_coasts(world: &World) {
+    for province_id in world.iter_storage::<Province>() {
+        let coordinate = province_id.get().coordinate;
         let is_ocean = province_id.get().terrain == Terrain::Ocean;
 
         for other_coord in coordinate.neighbors_iter() {
@@ -77,77 +435,322 @@ pub fn generate_world(world: &mut World) {
     }
 }
 
-pub fn create_test_world(world: &mut World) {
-    generate_world(world);
-    let religion_id = world.insert(Religion {
-        id: 0,
-        name: "Test Religion".to_owned(),
-    });
+// land provinces for each tin field, and warm coast for each murex bed
+pub const TIN_FIELD_LAND: usize = 1500;
+pub const MUREX_BED_COAST: usize = 150;
+pub const DEPOSIT_CLUSTER_RADIUS: isize = 2;
+// share of lead deposits that carry silver
+pub const ARGENTIFEROUS_SHARE: f32 = 0.4;
 
-    let mut language = Language::new();
-    language.name = language.generate_name(2);
-    let culture_name = language.generate_name(2);
-    let language_id = world.insert(language);
-    let culture_id = world.insert(Culture {
-        id: 0,
-        name: culture_name,
-        language: language_id.clone(),
-        religion: religion_id.clone(),
-        features: Vec::new(),
-    });
+// scattered deposits, by the lay of the land
+fn deposit_chances(province: &Province) -> Vec<(GoodType, f32)> {
+    let mut chances = Vec::new();
+    let warm = province.climate != Climate::Cold;
+    match province.terrain {
+        Terrain::Mountains => {
+            chances.push((Copper, 0.12));
+            chances.push((Lead, 0.06));
+            chances.push((Iron, 0.05));
+            chances.push((Gold, 0.02));
+            if warm {
+                chances.push((Marble, 0.03));
+            }
+        }
+        Terrain::Hills => {
+            chances.push((Copper, 0.06));
+            chances.push((Iron, 0.04));
+            chances.push((Lead, 0.02));
+            if warm {
+                chances.push((Marble, 0.04));
+            }
+        }
+        // bog iron
+        Terrain::Marsh | Terrain::Forest => chances.push((Iron, 0.03)),
+        Terrain::Desert => chances.push((Salt, if province.coastal { 0.3 } else { 0.06 })),
+        _ => {}
+    }
+    if province.coastal && province.climate == Climate::Dry && province.terrain != Terrain::Desert {
+        // salt pans
+        chances.push((Salt, 0.15));
+    }
+    if province.river.is_some() && province.terrain == Terrain::Hills {
+        // gold washed down from the mountains
+        chances.push((Gold, 0.01));
+    }
+    chances
+}
 
-    // create provinces
-    for i in 0..MAP_SIZE {
-        for j in 0..MAP_SIZE {
-            let coordinate = Coordinate::new(i - (j / 2), j);
+// a few seeds that spill over into suitable land nearby
+fn cluster_deposits(world: &World, good: GoodType, candidates: &[Coordinate], seeds: usize, rng: &mut StdRng) {
+    let suitable = candidates.iter().cloned().collect::<HashSet<_>>();
+    for &seed in candidates.choose_multiple(rng, seeds).collect::<Vec<_>>() {
+        let mut frontier = vec![seed];
+        let mut seen = HashSet::new();
+        seen.insert(seed);
+        while let Some(coordinate) = frontier.pop() {
+            let province = world.get_province_coordinate(coordinate).unwrap();
+            if !province.get().deposits.contains(&good) {
+                province.get_mut().deposits.push(good);
+            }
+            let mut neighbors = coordinate
+                .neighbors_iter()
+                .filter(|n| suitable.contains(n) && n.dist(seed) <= DEPOSIT_CLUSTER_RADIUS)
+                .collect::<Vec<_>>();
+            neighbors.sort_by_key(|c| (c.y, c.x));
+            for neighbor in neighbors {
+                if seen.insert(neighbor) && rng.gen::<f32>() < 0.5 {
+                    frontier.push(neighbor);
+                }
+            }
+        }
+    }
+}
 
-            let province_id = world.get_province_coordinate(coordinate).unwrap();
+pub fn place_deposits(world: &World, rng: &mut StdRng) {
+    let mut land = world
+        .iter_storage::<Province>()
+        .filter(|p| p.get().terrain != Terrain::Ocean)
+        .map(|p| p.get().coordinate)
+        .collect::<Vec<_>>();
+    land.sort_by_key(|c| (c.y, c.x));
+    for coordinate in land.iter() {
+        let province_id = world.get_province_coordinate(*coordinate).unwrap();
+        let chances = deposit_chances(&province_id.get());
+        let mut province = province_id.get_mut();
+        for (good, chance) in chances {
+            if rng.gen::<f32>() < chance && !province.deposits.contains(&good) {
+                province.deposits.push(good);
+                if good == Lead && rng.gen::<f32>() < ARGENTIFEROUS_SHARE {
+                    province.deposits.push(Silver);
+                }
+            }
+        }
+    }
 
-            if province_id.get().terrain == Terrain::Ocean {
-                continue;
+    // tin is rare, and found in a handful of upland fields
+    let uplands = land
+        .iter()
+        .filter(|c| {
+            let terrain = world.get_province_coordinate(**c).unwrap().get().terrain;
+            terrain == Terrain::Hills || terrain == Terrain::Mountains
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let tin_fields = (land.len() / TIN_FIELD_LAND).max(1).min(uplands.len());
+    cluster_deposits(world, Tin, &uplands, tin_fields, rng);
+
+    // murex snails live off rocky, warm coasts
+    let warm_coasts = land
+        .iter()
+        .filter(|c| {
+            let province = world.get_province_coordinate(**c).unwrap();
+            let province = province.get();
+            province.coastal
+                && (province.climate == Climate::Mild || province.climate == Climate::Dry)
+                && province.terrain != Terrain::Marsh
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let murex_beds = (warm_coasts.len() / MUREX_BED_COAST).max(1).min(warm_coasts.len());
+    cluster_deposits(world, PurpleDye, &warm_coasts, murex_beds, rng);
+}
+
+// how far a starting chiefdom reaches from its seat
+pub const POLITY_RADIUS: isize = 4;
+
+// culture hearths sit on good land, spread apart from each other
+fn place_hearths(land: &[Coordinate], count: usize, world: &World, config: &WorldGenConfig, rng: &mut StdRng) -> Vec<Coordinate> {
+    let spacing = (config.width.min(config.height) as f32 / (2.0 * (count as f32).sqrt())) as isize;
+    let good = land
+        .iter()
+        .filter(|c| {
+            let terrain = world.get_province_coordinate(**c).unwrap().get().terrain;
+            terrain == Terrain::Plains || terrain == Terrain::Hills || terrain == Terrain::Forest
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let candidates = if good.is_empty() { land } else { &good };
+    let mut hearths: Vec<Coordinate> = Vec::new();
+    for _ in 0..count {
+        let mut best = candidates[rng.gen_range(0..candidates.len())];
+        for _ in 0..50 {
+            if hearths.iter().all(|h| h.dist(best) >= spacing) {
+                break;
             }
+            best = candidates[rng.gen_range(0..candidates.len())];
+        }
+        hearths.push(best);
+    }
+    hearths
+}
+
+// cultures spread out from their hearths over land, slowed by rough terrain
+fn culture_regions(land: &[Coordinate], hearths: &[Coordinate], world: &World) -> HashMap<Coordinate, usize> {
+    let field = distance_field(world, hearths, &LandMovement, None);
+    land.iter()
+        .map(|coordinate| {
+            // islands out of reach go to whoever is closest
+            let nearest = field
+                .nearest(*coordinate)
+                .unwrap_or_else(|| (0..hearths.len()).min_by_key(|i| hearths[*i].dist(*coordinate)).unwrap());
+            (*coordinate, nearest)
+        })
+        .collect()
+}
 
-            if random::<f32>() > 0.9 {
-                let polity_id = add_polity(world, language_id.get().generate_name(2), culture_id.clone(), PolityLevel::Tribe);
-                add_test_settlement(world, culture_id.clone(), province_id.clone(), polity_id);
+fn starting_level(settlements: usize) -> PolityLevel {
+    if settlements >= KINGDOM_SETTLEMENTS {
+        PolityLevel::Kingdom
+    } else if settlements >= CHIEFDOM_SETTLEMENTS {
+        PolityLevel::Chiefdom
+    } else {
+        PolityLevel::Tribe
+    }
+}
+
+pub fn create_test_world(world: &mut World, config: &WorldGenConfig) {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    world.date = Date::from_year(config.start_year);
+    generate_world(world, config, &mut rng);
+
+    let mut land = world
+        .iter_storage::<Province>()
+        .filter(|p| p.get().terrain != Terrain::Ocean)
+        .map(|p| p.get().coordinate)
+        .collect::<Vec<_>>();
+    if land.is_empty() {
+        return;
+    }
+    land.sort_by_key(|c| (c.y, c.x));
+    let hearths = place_hearths(&land, config.cultures.max(1), world, config, &mut rng);
+    let mut cultures: Vec<CultureId> = Vec::new();
+    for (i, hearth) in hearths.iter().enumerate() {
+        // the first cultures found the religions, the rest follow their nearest neighbor
+        let religion = if i < config.religions {
+            None
+        } else {
+            (0..config.religions)
+                .min_by_key(|j| hearths[*j].dist(*hearth))
+                .map(|j| cultures[j].get().religion.clone())
+        };
+        let seed = rng.gen();
+        cultures.push(add_culture(world, seed, religion, &mut rng));
+    }
+    let regions = culture_regions(&land, &hearths, world);
+
+    let mut sites = land
+        .iter()
+        .filter(|_| rng.gen::<f32>() < config.settlement_density)
+        .map(|c| (*c, regions[c]))
+        .collect::<Vec<_>>();
+    sites.shuffle(&mut rng);
+    // some settlements start out ruling their neighbors
+    let mut claimed: HashSet<Coordinate> = HashSet::new();
+    let mut polities: Vec<(usize, Vec<Coordinate>)> = Vec::new();
+    for &(seat, region) in sites.iter() {
+        if !claimed.insert(seat) {
+            continue;
+        }
+        let mut members = vec![seat];
+        if rng.gen::<f32>() < config.chiefdom_chance {
+            let size = rng.gen_range(2..=config.max_polity_size.max(2));
+            let mut nearby = sites
+                .iter()
+                .filter(|(c, r)| *r == region && !claimed.contains(c) && c.dist(seat) <= POLITY_RADIUS)
+                .map(|(c, _)| *c)
+                .collect::<Vec<_>>();
+            nearby.sort_by_key(|c| (c.dist(seat), c.y, c.x));
+            for c in nearby.into_iter().take(size - 1) {
+                claimed.insert(c);
+                members.push(c);
             }
         }
+        polities.push((region, members));
+    }
+    for (region, members) in polities {
+        let culture_id = cultures[region].clone();
+        let name = culture_id.get().language.get().generate_toponym();
+        let polity_id = add_polity(world, name, culture_id.clone(), starting_level(members.len()), &mut rng);
+        for coordinate in members {
+            let province_id = world.get_province_coordinate(coordinate).unwrap();
+            add_settlement(world, culture_id.clone(), province_id, polity_id.clone(), config.pop_size, &mut rng);
+        }
     }
+    UpdateTerritoryCommand.run(world);
+    assign_holy_sites(world, &mut rng);
+}
+
+pub fn add_culture(world: &mut World, seed: u64, religion: Option<ReligionId>, rng: &mut impl Rng) -> CultureId {
+    let mut language = Language::new(seed, world.date);
+    language.name = language.generate_name(3);
+    let name = language.demonym(&language.name);
+    let language = world.insert(language);
+    let religion = match religion {
+        Some(religion) => religion,
+        None => add_religion(world, &language, Doctrine::random_doctrines(2, rng), Vec::new(), rng),
+    };
+    world.insert(Culture {
+        id: 0,
+        name,
+        language,
+        religion,
+        features: Vec::new(),
+        gender_law: GenderLaw::random(rng),
+        parent: None,
+        founded: world.date,
+        martial_history: 0.0,
+    })
 }
 
-pub fn add_polity(world: &mut World, name: String, culture_id: CultureId, level: PolityLevel) -> PolityId {
-    let age = positive_isample(8, 45);
-    let leader = culture_id.get().generate_character(Sex::Male, age, world);
+pub fn add_polity(world: &mut World, name: String, culture_id: CultureId, level: PolityLevel, rng: &mut impl Rng) -> PolityId {
+    let age = positive_isample_with(8, 45, rng);
+    let sex = culture_id.get().gender_law.ruler_sex_with(rng);
+    let leader = culture_id.get().generate_character(sex, age, world, rng);
+    add_polity_with_leader(world, name, culture_id, level, leader)
+}
+
+pub fn add_polity_with_leader(world: &mut World, name: String, culture_id: CultureId, level: PolityLevel, leader: CharacterId) -> PolityId {
+    found_dynasty(&leader, world);
     let polity_id = world.insert(Polity {
         id: 0,
         name,
         primary_culture: culture_id.clone(),
         capital: None,
-        level: PolityLevel::Tribe,
+        level,
         leader: leader.clone(),
-        successor_law: SuccessorLaw::Election,
+        successor_law: level_successor_law(level),
+        gender_law: culture_id.get().gender_law,
+        regent: None,
+        treasury: GoodStorage(HashMap::new()),
+        religion: culture_id.get().religion.clone(),
     });
     leader.get_mut().titles.push(Title::PolityLeader(polity_id.clone()));
     polity_id
 }
 
-fn add_test_settlement(world: &mut World, culture_id: CultureId, province_id: ProvinceId, polity_id: PolityId) -> SettlementId {
-    add_settlement(world, culture_id, province_id, polity_id, 100)
-}
-pub fn add_settlement(world: &mut World, culture_id: CultureId, province_id: ProvinceId, polity_id: PolityId, size: isize) -> SettlementId {
-    let sites = province_id.get().generate_sites(world, 3);
+pub fn add_settlement(
+    world: &mut World,
+    culture_id: CultureId,
+    province_id: ProvinceId,
+    polity_id: PolityId,
+    size: isize,
+    rng: &mut impl Rng,
+) -> SettlementId {
+    let sites = province_id.get().generate_sites(world, 3, rng);
     let leader = if polity_id.get().capital.is_none() {
         polity_id.get().leader.clone()
     } else {
-        let age = positive_isample(8, 45);
-        culture_id.get().generate_character(Sex::Male, age, world)
+        let age = positive_isample_with(8, 45, rng);
+        let sex = polity_id.get().gender_law.ruler_sex_with(rng);
+        culture_id.get().generate_character(sex, age, world, rng)
     };
 
     let settlement_id = world.insert_settlement(Settlement {
         id: 0,
-        name: culture_id.get().language.get().generate_name(4),
+        name: culture_id.get().language.get().generate_toponym(),
         pops: vec![],
         features: HashSet::new(),
+        buildings: HashSet::new(),
         primary_culture: culture_id.clone(),
         province: province_id.clone(),
         level: SettlementLevel::Village,
@@ -160,6 +763,7 @@ pub fn add_settlement(world: &mut World, culture_id: CultureId, province_id: Pro
         size,
         farmed_good: Some(Wheat),
         culture: culture_id.clone(),
+        religion: culture_id.get().religion.clone(),
         settlement: settlement_id.clone(),
         province: province_id.clone(),
         satiety: Satiety {
@@ -170,6 +774,7 @@ pub fn add_settlement(world: &mut World, culture_id: CultureId, province_id: Pro
         owned_goods: GoodStorage(HashMap::new()),
         migration_status: None,
         polity: polity_id.clone(),
+        unrest: 0.0,
     });
     let site = pop_id.get().evaluate_sites(sites, world, province_id.clone());
     settlement_id.get_mut().features = site.features;